@@ -35,6 +35,25 @@
 //!     .connect(i2c_interface);
 //! ```
 //!
+//! Append vendor-specific raw commands to the init sequence
+//!
+//! ```rust,ignore
+//! Builder::new()
+//!     .with_init_sequence(InitSequence::Append(&[0xD9, 0x22, 0xDB, 0x34]))
+//!     .connect(interface);
+//! ```
+//!
+//! Set up the init-time parameters of the panel, which are validated and sent by `init`
+//!
+//! ```rust,ignore
+//! Builder::new()
+//!     .with_clock(0xf, 1)
+//!     .with_precharge(2, 15)
+//!     .with_vcomh(VcomhLevel::V084)
+//!     .with_contrast(0xcf)
+//!     .connect(interface);
+//! ```
+//!
 //! The above examples will produce a [RawMode](../mode/raw/struct.RawMode.html) instance
 //! by default. You need to coerce them into a mode by specifying a type on assignment. For
 //! example, to use [`GraphicsMode` mode](../mode/graphics/struct.GraphicsMode.html):
@@ -46,18 +65,28 @@
 use core::marker::PhantomData;
 use hal::{self, digital::v2::OutputPin};
 
+#[cfg(feature = "async")]
+use crate::mode::{
+    asynch::{AsyncGraphicsMode, CommandQueue},
+    graphics::GraphicsMode,
+};
 use crate::{
+    command::VcomhLevel,
+    config::{InitConfig, InitSequence},
     displayrotation::DisplayRotation,
-    displaysize::DisplaySize,
+    displaysize::{DisplaySize128x64, PanelSize},
     mode::{displaymode::DisplayMode, raw::RawMode},
     properties::DisplayProperties,
+    wiring::Wiring,
 };
 
 /// Builder struct. Driver options and interface are set using its methods.
 #[derive(Clone, Copy)]
-pub struct Builder {
-    display_size: DisplaySize,
+pub struct Builder<SIZE = DisplaySize128x64> {
+    size: SIZE,
     rotation: DisplayRotation,
+    mirror: (bool, bool),
+    config: InitConfig,
 }
 
 impl Default for Builder {
@@ -70,18 +99,26 @@ impl Builder {
     /// Create new builder with a default size of 128 x 64 pixels and no rotation.
     pub fn new() -> Builder {
         Builder {
-            display_size: DisplaySize::Display128x64,
+            size: DisplaySize128x64,
             rotation: DisplayRotation::Rotate0,
+            mirror: (false, false),
+            config: InitConfig::default(),
         }
     }
 }
 
-impl Builder {
-    /// Set the size of the display. Supported sizes are defined by [DisplaySize].
-    pub fn with_size(self, display_size: DisplaySize) -> Self {
-        Self {
-            display_size,
-            ..self
+impl<SIZE> Builder<SIZE>
+where
+    SIZE: PanelSize,
+{
+    /// Set the size of the display to one of the [PanelSize] types, which becomes part of the type
+    /// of the driver. Defaults to [DisplaySize128x64].
+    pub fn with_size<NSIZE: PanelSize>(self, size: NSIZE) -> Builder<NSIZE> {
+        Builder {
+            size,
+            rotation: self.rotation,
+            mirror: self.mirror,
+            config: self.config,
         }
     }
 
@@ -90,21 +127,132 @@ impl Builder {
         Self { rotation, ..self }
     }
 
+    /// Mirror the display horizontally and/or vertically after rotating it. Defaults to no
+    /// mirroring.
+    pub fn with_mirror(self, horizontal: bool, vertical: bool) -> Self {
+        Self {
+            mirror: (horizontal, vertical),
+            ..self
+        }
+    }
+
+    /// Set the oscillator frequency from 0 to 15, increasing with higher values, and the display
+    /// clock divide ratio from 1 to 16. Defaults to a frequency of 10 and a divide ratio of 1.
+    pub fn with_clock(self, frequency: u8, divide_ratio: u8) -> Self {
+        self.with_config(InitConfig {
+            clock: (frequency, divide_ratio.wrapping_sub(1)),
+            ..self.config
+        })
+    }
+
+    /// Set phase 1 and phase 2 of the pre-charge period from 1 to 15 display clocks each.
+    /// Defaults to 3 and 13 display clocks.
+    pub fn with_precharge(self, phase1: u8, phase2: u8) -> Self {
+        self.with_config(InitConfig {
+            precharge: (phase1, phase2),
+            ..self.config
+        })
+    }
+
+    /// Set the Vcomh deselect level. Defaults to [VcomhLevel::V078].
+    pub fn with_vcomh(self, vcomh: VcomhLevel) -> Self {
+        self.with_config(InitConfig {
+            vcomh,
+            ..self.config
+        })
+    }
+
+    /// Set the contrast the display is initialised with. Defaults to 0x6f.
+    pub fn with_contrast(self, contrast: u8) -> Self {
+        self.with_config(InitConfig {
+            contrast,
+            ..self.config
+        })
+    }
+
+    /// Set the multiplex ratio from 16 to 64, which must not be lower than the height of the
+    /// display. Defaults to the multiplex ratio of the display size.
+    pub fn with_multiplex(self, multiplex: u8) -> Self {
+        self.with_config(InitConfig {
+            multiplex: Some(multiplex),
+            ..self.config
+        })
+    }
+
+    /// Set the vertical display offset from 0 to 63 rows. Defaults to the offset of the display
+    /// size.
+    pub fn with_display_offset(self, display_offset: u8) -> Self {
+        self.with_config(InitConfig {
+            display_offset: Some(display_offset),
+            ..self.config
+        })
+    }
+
+    /// Set the display RAM row shown on the first row of the display from 0 to 63. Defaults to 0.
+    pub fn with_start_line(self, start_line: u8) -> Self {
+        self.with_config(InitConfig {
+            start_line,
+            ..self.config
+        })
+    }
+
+    /// Set how the panel is wired to the controller. Defaults to no remapping and the COM pin
+    /// configuration of the display size.
+    pub fn with_wiring(self, wiring: Wiring) -> Self {
+        self.with_config(InitConfig {
+            wiring,
+            ..self.config
+        })
+    }
+
+    /// Send a custom sequence of raw command bytes during `init`, either instead of or after the
+    /// built-in init sequence.
+    pub fn with_init_sequence(self, sequence: InitSequence) -> Self {
+        self.with_config(InitConfig {
+            sequence: Some(sequence),
+            ..self.config
+        })
+    }
+
+    fn with_config(self, config: InitConfig) -> Self {
+        Self { config, ..self }
+    }
+
     /// Finish the builder and use the given interface to communicate with the display.
-    pub fn connect<DI>(self, interface: DI) -> DisplayMode<RawMode<DI>>
+    pub fn connect<DI>(self, interface: DI) -> DisplayMode<RawMode<DI, SIZE>>
     where
         DI: display_interface::WriteOnlyDataCommand,
     {
-        let properties = DisplayProperties::new(
-            interface,
-            self.display_size,
-            self.rotation,
-        );
-        DisplayMode::<RawMode<DI>>::new(properties)
+        let properties = DisplayProperties::new(interface, self.size, self.rotation)
+            .with_mirror(self.mirror)
+            .with_config(self.config);
+        DisplayMode::<RawMode<DI, SIZE>>::new(properties)
+    }
+
+    /// Finish the builder and use the given async interface to communicate with the display.
+    /// The display buffer is an inline array, which is sized to the display by default:
+    ///
+    /// ```rust,ignore
+    /// let display: AsyncGraphicsMode<_> = Builder::new().connect_async(interface);
+    /// ```
+    #[cfg(feature = "async")]
+    pub fn connect_async<DI, const N: usize>(
+        self,
+        interface: DI,
+    ) -> AsyncGraphicsMode<DI, SIZE, [u8; N]>
+    where
+        DI: display_interface_async::AsyncWriteOnlyDataCommand,
+    {
+        let mode: GraphicsMode<_, SIZE, [u8; N]> = self.connect(CommandQueue::new()).into();
+
+        AsyncGraphicsMode::new(mode, interface)
     }
 }
 
 /// Represents an unused output pin.
+///
+/// With the `embedded-hal-1` feature, the embedded-hal 1.0 `OutputPin` trait is implemented as
+/// well if `PinE` implements its `Error` trait, e.g. for `NoOutputPin<Infallible>`.
 #[derive(Clone, Copy)]
 pub struct NoOutputPin<PinE = ()> {
     _m: PhantomData<PinE>,
@@ -117,6 +265,12 @@ impl<PinE> NoOutputPin<PinE> {
     }
 }
 
+impl<PinE> Default for NoOutputPin<PinE> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<PinE> OutputPin for NoOutputPin<PinE> {
     type Error = PinE;
     fn set_low(&mut self) -> Result<(), PinE> {
@@ -127,9 +281,36 @@ impl<PinE> OutputPin for NoOutputPin<PinE> {
     }
 }
 
+#[cfg(feature = "embedded-hal-1")]
+impl<PinE> embedded_hal_1::digital::ErrorType for NoOutputPin<PinE>
+where
+    PinE: embedded_hal_1::digital::Error,
+{
+    type Error = PinE;
+}
+
+#[cfg(feature = "embedded-hal-1")]
+impl<PinE> embedded_hal_1::digital::OutputPin for NoOutputPin<PinE>
+where
+    PinE: embedded_hal_1::digital::Error,
+{
+    fn set_low(&mut self) -> Result<(), PinE> {
+        Ok(())
+    }
+    fn set_high(&mut self) -> Result<(), PinE> {
+        Ok(())
+    }
+}
+
 #[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
 mod tests {
-    use super::NoOutputPin;
+    use super::{Builder, NoOutputPin};
+    use crate::{
+        displaysize::DisplaySize128x32,
+        error::{Error, Operation},
+        test_helpers::{command, RecordingInterface},
+    };
     use embedded_hal::digital::v2::OutputPin;
 
     enum SomeError {}
@@ -146,4 +327,36 @@ mod tests {
 
         assert!(true);
     }
+
+    #[test]
+    fn sends_init_parameters() {
+        let builder = Builder::new()
+            .with_size(DisplaySize128x32)
+            .with_clock(0xc, 3)
+            .with_multiplex(48)
+            .with_display_offset(5)
+            .with_start_line(3)
+            .with_precharge(2, 15);
+
+        let mut properties = builder.connect(RecordingInterface::new()).release();
+        properties.init_column_mode().unwrap();
+
+        let expected = [
+            0xd5, 0xc2, 0xa8, 0x2f, 0xd3, 0x05, 0x43, 0xda, 0x02, 0x20, 0x02, 0xa1, 0xc8, 0x81,
+            0x6f, 0xd9, 0xf2, 0xdb, 0x34, 0x2e, 0xaf,
+        ]
+        .map(command);
+        assert_eq!(properties.iface_mut().recorded(), expected);
+
+        // A divide ratio of 0 is rejected without sending anything
+        let mut properties = builder
+            .with_clock(0xc, 0)
+            .connect(RecordingInterface::new())
+            .release();
+        assert!(matches!(
+            properties.init_column_mode(),
+            Err(Error::InvalidParameter(Operation::Init))
+        ));
+        assert_eq!(properties.iface_mut().len, 0);
+    }
 }
@@ -1,10 +1,17 @@
-use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+//! ssd1309 Commands
+//!
+//! Low-level access to the command set of the controller. Commands can be sent to any
+//! `display-interface` implementor with [Command::send], or through
+//! [DisplayProperties::send_command](crate::properties::DisplayProperties::send_command), which
+//! takes care of the command lock. Note that the driver doesn't track the state changed by
+//! commands sent this way.
+
+use core::convert::TryFrom;
 
-/// ssd1309 Commands
+use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
 
 /// Commands
-#[derive(Debug)]
-#[allow(dead_code)]
+#[derive(Debug, Clone, Copy)]
 pub enum Command {
     /// Set contrast. Higher number is higher contrast. Default = 0x7F
     Contrast(u8),
@@ -21,6 +28,13 @@ pub enum Command {
     ColumnAddressHigh(u8),
     /// Set page address
     PageAddress(Page),
+    /// Set GDDRAM addressing mode
+    AddressMode(AddrMode),
+    /// Set the start and end column of the window used by horizontal and vertical addressing
+    /// mode
+    ColumnRange(u8, u8),
+    /// Set the start and end page of the window used by horizontal and vertical addressing mode
+    PageRange(Page, Page),
     /// Set display start line from 0-63
     StartLine(u8),
     /// Reverse columns from 127-0
@@ -33,8 +47,8 @@ pub enum Command {
     DisplayOffset(u8),
     /// Setup com hardware configuration
     /// First value indicates sequential (false) or alternative (true)
-    /// pin configuration.
-    ComPinConfig(bool),
+    /// pin configuration. Second value enables the COM left/right remap.
+    ComPinConfig(bool, bool),
     /// Set up display clock.
     /// First value is oscillator frequency, increasing with higher value
     /// Second value is divide ratio - 1
@@ -43,8 +57,35 @@ pub enum Command {
     PreChargePeriod(u8, u8),
     /// Set Vcomh Deselect level
     VcomhDeselect(VcomhLevel),
+    /// Setup continuous horizontal scroll.
+    /// Values are scroll direction, start page, end page, interval between scroll steps, start
+    /// column and end column.
+    HorizontalScrollSetup(ScrollDirection, Page, Page, NFrames, u8, u8),
+    /// Setup continuous vertical scroll, optionally combined with a horizontal scroll.
+    /// Values are horizontal scroll direction (if any), start page, end page, interval between
+    /// scroll steps, vertical offset per step in rows, start column and end column.
+    VerticalScrollSetup(Option<ScrollDirection>, Page, Page, NFrames, u8, u8, u8),
+    /// Set the vertical scroll area.
+    /// First value is the number of rows in the top fixed area, second value is the number of
+    /// rows in the scroll area below it.
+    VerticalScrollArea(u8, u8),
+    /// Scroll the content of a page and column range horizontally by one column.
+    /// Values are scroll direction, start page, end page, start column and end column.
+    ContentScroll(ScrollDirection, Page, Page, u8, u8),
     /// Enable or disable scrolling
     ScrollActive(bool),
+    /// Set fade out or blinking mode.
+    /// Second value is the interval between brightness steps in units of 8 frames, minus 1.
+    /// This command is inherited from the SSD1306 and is not listed in the SSD1309 datasheet.
+    Fade(FadeMode, u8),
+    /// Enable or disable zoom in mode, which doubles the height of every displayed row.
+    /// This command is inherited from the SSD1306 and is not listed in the SSD1309 datasheet.
+    Zoom(bool),
+    /// Set the state of the GPIO pin
+    Gpio(GpioMode),
+    /// Lock or unlock the controller. While locked, all commands except for unlocking are
+    /// ignored.
+    Lock(bool),
     /// NOOP
     Noop,
 }
@@ -55,36 +96,123 @@ impl Command {
     where
         DI: WriteOnlyDataCommand,
     {
-        // Transform command into a fixed size array of 7 u8 and the real length for sending
-        let (data, len) = match self {
-            Command::Contrast(val) => ([0x81, val, 0, 0, 0, 0, 0], 2),
-            Command::AllOn(on) => ([0xA4 | (on as u8), 0, 0, 0, 0, 0, 0], 1),
-            Command::Invert(inv) => ([0xA6 | (inv as u8), 0, 0, 0, 0, 0, 0], 1),
-            Command::DisplayOn(on) => ([0xAE | (on as u8), 0, 0, 0, 0, 0, 0], 1),
-            Command::ColumnAddressLow(addr) => ([0xF & addr, 0, 0, 0, 0, 0, 0], 1),
-            Command::ColumnAddressHigh(addr) => ([0x10 | (0xF & addr), 0, 0, 0, 0, 0, 0], 1),
-            Command::PageAddress(page) => ([0xB0 | (page as u8), 0, 0, 0, 0, 0, 0], 1),
-            Command::StartLine(line) => ([0x40 | (0x3F & line), 0, 0, 0, 0, 0, 0], 1),
-            Command::SegmentRemap(remap) => ([0xA0 | (remap as u8), 0, 0, 0, 0, 0, 0], 1),
-            Command::Multiplex(ratio) => ([0xA8, ratio, 0, 0, 0, 0, 0], 2),
-            Command::ReverseComDir(rev) => ([0xC0 | ((rev as u8) << 3), 0, 0, 0, 0, 0, 0], 1),
-            Command::DisplayOffset(offset) => ([0xD3, offset, 0, 0, 0, 0, 0], 2),
-            Command::ComPinConfig(alt) => ([0xDA, 0x02 | ((alt as u8) << 4), 0, 0, 0, 0, 0], 2),
-            Command::DisplayClockDiv(fosc, div) => {
-                ([0xD5, ((0xF & fosc) << 4) | (0xF & div), 0, 0, 0, 0, 0], 2)
-            }
-            Command::PreChargePeriod(phase1, phase2) => (
-                [0xD9, ((0xF & phase2) << 4) | (0xF & phase1), 0, 0, 0, 0, 0],
-                2,
-            ),
-            Command::VcomhDeselect(level) => ([0xDB, (level as u8) << 2, 0, 0, 0, 0, 0], 2),
-            Command::ScrollActive(active) => ([0x2E | (active as u8), 0, 0, 0, 0, 0, 0], 1),
-            Command::Noop => ([0xE3, 0, 0, 0, 0, 0, 0], 1),
-        };
+        let (data, len) = self.encode();
 
         // Send command over the interface
         iface.send_commands(DataFormat::U8(&data[0..len]))
     }
+
+    /// Get the length in bytes, including arguments, of the command starting with `opcode`, or
+    /// `None` if the opcode is unknown
+    pub(crate) fn len_of(opcode: u8) -> Option<usize> {
+        match opcode {
+            0x26 | 0x27 | 0x29 | 0x2A | 0x2C | 0x2D => Some(8),
+            0x21 | 0x22 | 0xA3 => Some(3),
+            0x20 | 0x23 | 0x81 | 0xA8 | 0xD3 | 0xD5 | 0xD6 | 0xD9 | 0xDA | 0xDB | 0xDC | 0xFD => {
+                Some(2)
+            }
+            0x00..=0x1F
+            | 0x2E
+            | 0x2F
+            | 0x40..=0x7F
+            | 0xA0
+            | 0xA1
+            | 0xA4..=0xA7
+            | 0xAE
+            | 0xAF
+            | 0xB0..=0xB7
+            | 0xC0..=0xCF
+            | 0xE3 => Some(1),
+            _ => None,
+        }
+    }
+
+    /// Transform command into a fixed size array of 8 u8 and the real length for sending
+    pub fn encode(self) -> ([u8; 8], usize) {
+        match self {
+            Command::Contrast(val) => ([0x81, val, 0, 0, 0, 0, 0, 0], 2),
+            Command::AllOn(on) => ([0xA4 | (on as u8), 0, 0, 0, 0, 0, 0, 0], 1),
+            Command::Invert(inv) => ([0xA6 | (inv as u8), 0, 0, 0, 0, 0, 0, 0], 1),
+            Command::DisplayOn(on) => ([0xAE | (on as u8), 0, 0, 0, 0, 0, 0, 0], 1),
+            Command::ColumnAddressLow(addr) => ([0xF & addr, 0, 0, 0, 0, 0, 0, 0], 1),
+            Command::ColumnAddressHigh(addr) => ([0x10 | (0xF & addr), 0, 0, 0, 0, 0, 0, 0], 1),
+            Command::PageAddress(page) => ([0xB0 | (page as u8), 0, 0, 0, 0, 0, 0, 0], 1),
+            Command::AddressMode(mode) => ([0x20, mode as u8, 0, 0, 0, 0, 0, 0], 2),
+            Command::ColumnRange(start, end) => {
+                ([0x21, 0x7F & start, 0x7F & end, 0, 0, 0, 0, 0], 3)
+            }
+            Command::PageRange(start, end) => ([0x22, start as u8, end as u8, 0, 0, 0, 0, 0], 3),
+            Command::StartLine(line) => ([0x40 | (0x3F & line), 0, 0, 0, 0, 0, 0, 0], 1),
+            Command::SegmentRemap(remap) => ([0xA0 | (remap as u8), 0, 0, 0, 0, 0, 0, 0], 1),
+            Command::Multiplex(ratio) => ([0xA8, ratio, 0, 0, 0, 0, 0, 0], 2),
+            Command::ReverseComDir(rev) => ([0xC0 | ((rev as u8) << 3), 0, 0, 0, 0, 0, 0, 0], 1),
+            Command::DisplayOffset(offset) => ([0xD3, offset, 0, 0, 0, 0, 0, 0], 2),
+            Command::ComPinConfig(alt, remap) => {
+                let config = 0x02 | ((alt as u8) << 4) | ((remap as u8) << 5);
+                ([0xDA, config, 0, 0, 0, 0, 0, 0], 2)
+            }
+            Command::DisplayClockDiv(fosc, div) => (
+                [0xD5, ((0xF & fosc) << 4) | (0xF & div), 0, 0, 0, 0, 0, 0],
+                2,
+            ),
+            Command::PreChargePeriod(phase1, phase2) => {
+                let period = ((0xF & phase2) << 4) | (0xF & phase1);
+                ([0xD9, period, 0, 0, 0, 0, 0, 0], 2)
+            }
+            Command::VcomhDeselect(level) => ([0xDB, (level as u8) << 2, 0, 0, 0, 0, 0, 0], 2),
+            Command::HorizontalScrollSetup(dir, start, end, rate, start_col, end_col) => (
+                [
+                    0x26 | (dir as u8),
+                    0,
+                    start as u8,
+                    rate as u8,
+                    end as u8,
+                    0,
+                    start_col,
+                    end_col,
+                ],
+                8,
+            ),
+            Command::VerticalScrollSetup(dir, start, end, rate, offset, start_col, end_col) => (
+                [
+                    0x29 + dir.map_or(0, |dir| dir as u8),
+                    dir.is_some() as u8,
+                    start as u8,
+                    rate as u8,
+                    end as u8,
+                    0x3F & offset,
+                    start_col,
+                    end_col,
+                ],
+                8,
+            ),
+            Command::VerticalScrollArea(fixed, rows) => {
+                ([0xA3, 0x3F & fixed, 0x7F & rows, 0, 0, 0, 0, 0], 3)
+            }
+            Command::ContentScroll(dir, start, end, start_col, end_col) => (
+                [
+                    0x2C | (dir as u8),
+                    0,
+                    start as u8,
+                    1,
+                    end as u8,
+                    0,
+                    start_col,
+                    end_col,
+                ],
+                8,
+            ),
+            Command::ScrollActive(active) => ([0x2E | (active as u8), 0, 0, 0, 0, 0, 0, 0], 1),
+            Command::Fade(mode, interval) => {
+                let fade = ((mode as u8) << 4) | (0xF & interval);
+                ([0x23, fade, 0, 0, 0, 0, 0, 0], 2)
+            }
+            Command::Zoom(on) => ([0xD6, on as u8, 0, 0, 0, 0, 0, 0], 2),
+            Command::Gpio(mode) => ([0xDC, mode as u8, 0, 0, 0, 0, 0, 0], 2),
+            Command::Lock(lock) => ([0xFD, 0x12 | ((lock as u8) << 2), 0, 0, 0, 0, 0, 0], 2),
+            Command::Noop => ([0xE3, 0, 0, 0, 0, 0, 0, 0], 1),
+        }
+    }
 }
 
 /// Display page
@@ -108,9 +236,10 @@ pub enum Page {
     Page7 = 7,
 }
 
-impl From<u8> for Page {
-    fn from(val: u8) -> Page {
-        match val / 8 {
+impl Page {
+    /// Get the page containing a row, clamping rows below the display RAM to the last page
+    pub(crate) fn containing(row: u8) -> Page {
+        match row / 8 {
             0 => Page::Page0,
             1 => Page::Page1,
             2 => Page::Page2,
@@ -118,26 +247,54 @@ impl From<u8> for Page {
             4 => Page::Page4,
             5 => Page::Page5,
             6 => Page::Page6,
-            7 => Page::Page7,
-            _ => panic!("Page too high"),
+            _ => Page::Page7,
         }
     }
 }
 
+/// Error returned when converting a row outside of the display RAM into a [Page]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RowOutOfRange(pub u8);
+
+impl TryFrom<u8> for Page {
+    type Error = RowOutOfRange;
+
+    /// Get the page containing a row from 0 to 63
+    fn try_from(row: u8) -> Result<Page, RowOutOfRange> {
+        if row > 63 {
+            return Err(RowOutOfRange(row));
+        }
+
+        Ok(Page::containing(row))
+    }
+}
+
+/// GDDRAM addressing mode
+#[derive(Debug, Clone, Copy)]
+pub enum AddrMode {
+    /// Horizontal addressing mode. The column address wraps to the next page at the end of the
+    /// column range.
+    Horizontal = 0b00,
+    /// Vertical addressing mode. The page address wraps to the next column at the end of the
+    /// page range.
+    Vertical = 0b01,
+    /// Page addressing mode. The column address wraps within the current page.
+    Page = 0b10,
+}
+
 /// Frame interval
 #[derive(Debug, Clone, Copy)]
-#[allow(dead_code)]
 pub enum NFrames {
+    /// 1 Frame
+    F1 = 0b111,
     /// 2 Frames
-    F2 = 0b111,
+    F2 = 0b100,
     /// 3 Frames
-    F3 = 0b100,
+    F3 = 0b101,
     /// 4 Frames
-    F4 = 0b101,
+    F4 = 0b110,
     /// 5 Frames
     F5 = 0b000,
-    /// 25 Frames
-    F25 = 0b110,
     /// 64 Frames
     F64 = 0b001,
     /// 128 Frames
@@ -146,9 +303,41 @@ pub enum NFrames {
     F256 = 0b011,
 }
 
+/// Horizontal scroll direction
+#[derive(Debug, Clone, Copy)]
+pub enum ScrollDirection {
+    /// Scroll content to the right
+    Right = 0,
+    /// Scroll content to the left
+    Left = 1,
+}
+
+/// Fade out and blinking mode
+#[derive(Debug, Clone, Copy)]
+pub enum FadeMode {
+    /// Fading and blinking disabled
+    Disabled = 0b00,
+    /// Fade out the display once
+    FadeOut = 0b10,
+    /// Fade the display out and in continuously
+    Blink = 0b11,
+}
+
+/// GPIO pin state
+#[derive(Debug, Clone, Copy)]
+pub enum GpioMode {
+    /// High impedance, input disabled
+    InputDisabled = 0b00,
+    /// High impedance, input enabled
+    InputEnabled = 0b01,
+    /// Output low
+    OutputLow = 0b10,
+    /// Output high
+    OutputHigh = 0b11,
+}
+
 /// Vcomh Deselect level
 #[derive(Debug, Clone, Copy)]
-#[allow(dead_code)]
 pub enum VcomhLevel {
     /// 0.65 * Vcc
     V064 = 0b0000,
@@ -157,3 +346,126 @@ pub enum VcomhLevel {
     /// 0.83 * Vcc
     V084 = 0b1111,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Command, FadeMode, GpioMode, NFrames, Page, ScrollDirection};
+
+    #[test]
+    fn encodes_multi_byte_commands() {
+        use ScrollDirection::{Left, Right};
+
+        let commands: [(Command, &[u8]); 22] = [
+            (Command::ColumnRange(8, 127), &[0x21, 0x08, 0x7F]),
+            (Command::ColumnRange(0x85, 0xFF), &[0x21, 0x05, 0x7F]),
+            (
+                Command::PageRange(Page::Page2, Page::Page7),
+                &[0x22, 0x02, 0x07],
+            ),
+            (
+                Command::HorizontalScrollSetup(
+                    Right,
+                    Page::Page1,
+                    Page::Page6,
+                    NFrames::F2,
+                    4,
+                    100,
+                ),
+                &[0x26, 0x00, 0x01, 0x04, 0x06, 0x00, 0x04, 0x64],
+            ),
+            (
+                Command::HorizontalScrollSetup(
+                    Left,
+                    Page::Page0,
+                    Page::Page7,
+                    NFrames::F256,
+                    0,
+                    127,
+                ),
+                &[0x27, 0x00, 0x00, 0x03, 0x07, 0x00, 0x00, 0x7F],
+            ),
+            (
+                Command::VerticalScrollSetup(
+                    None,
+                    Page::Page0,
+                    Page::Page7,
+                    NFrames::F5,
+                    1,
+                    0,
+                    127,
+                ),
+                &[0x29, 0x00, 0x00, 0x00, 0x07, 0x01, 0x00, 0x7F],
+            ),
+            (
+                Command::VerticalScrollSetup(
+                    Some(Right),
+                    Page::Page2,
+                    Page::Page3,
+                    NFrames::F3,
+                    63,
+                    8,
+                    9,
+                ),
+                &[0x29, 0x01, 0x02, 0x05, 0x03, 0x3F, 0x08, 0x09],
+            ),
+            (
+                Command::VerticalScrollSetup(
+                    Some(Left),
+                    Page::Page2,
+                    Page::Page3,
+                    NFrames::F128,
+                    0xFF,
+                    8,
+                    9,
+                ),
+                &[0x2A, 0x01, 0x02, 0x02, 0x03, 0x3F, 0x08, 0x09],
+            ),
+            (Command::VerticalScrollArea(8, 56), &[0xA3, 0x08, 0x38]),
+            (Command::VerticalScrollArea(0xFF, 0xFF), &[0xA3, 0x3F, 0x7F]),
+            (
+                Command::ContentScroll(Right, Page::Page6, Page::Page7, 8, 39),
+                &[0x2C, 0x00, 0x06, 0x01, 0x07, 0x00, 0x08, 0x27],
+            ),
+            (
+                Command::ContentScroll(Left, Page::Page0, Page::Page0, 0, 127),
+                &[0x2D, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x7F],
+            ),
+            (Command::ComPinConfig(false, false), &[0xDA, 0x02]),
+            (Command::ComPinConfig(true, false), &[0xDA, 0x12]),
+            (Command::ComPinConfig(false, true), &[0xDA, 0x22]),
+            (Command::ComPinConfig(true, true), &[0xDA, 0x32]),
+            (Command::Gpio(GpioMode::InputDisabled), &[0xDC, 0x00]),
+            (Command::Gpio(GpioMode::InputEnabled), &[0xDC, 0x01]),
+            (Command::Gpio(GpioMode::OutputLow), &[0xDC, 0x02]),
+            (Command::Gpio(GpioMode::OutputHigh), &[0xDC, 0x03]),
+            (Command::Lock(false), &[0xFD, 0x12]),
+            (Command::Lock(true), &[0xFD, 0x16]),
+        ];
+
+        for (command, bytes) in commands {
+            let (data, len) = command.encode();
+            assert_eq!(&data[..len], bytes, "{:?}", command);
+            assert_eq!(Command::len_of(data[0]), Some(len), "{:?}", command);
+        }
+    }
+
+    #[test]
+    fn encodes_fade_mode_and_interval() {
+        let fade = |mode, interval| {
+            let (data, len) = Command::Fade(mode, interval).encode();
+            assert_eq!(len, 2);
+
+            (data[0], data[1])
+        };
+
+        assert_eq!(fade(FadeMode::Disabled, 0), (0x23, 0x00));
+        assert_eq!(fade(FadeMode::FadeOut, 0), (0x23, 0x20));
+        assert_eq!(fade(FadeMode::Blink, 0), (0x23, 0x30));
+
+        // The interval sits in bits 3:0 without touching the mode
+        assert_eq!(fade(FadeMode::Disabled, 15), (0x23, 0x0F));
+        assert_eq!(fade(FadeMode::FadeOut, 5), (0x23, 0x25));
+        assert_eq!(fade(FadeMode::Blink, 15), (0x23, 0x3F));
+        assert_eq!(fade(FadeMode::Blink, 0x1F), (0x23, 0x3F));
+    }
+}
@@ -0,0 +1,162 @@
+//! Parameters sent to the display during initialisation
+
+use crate::{
+    command::{Command, VcomhLevel},
+    wiring::Wiring,
+};
+
+/// Init-time parameters of the display, set up through the [Builder](crate::Builder)
+#[derive(Clone, Copy)]
+pub(crate) struct InitConfig {
+    /// Oscillator frequency and divide ratio - 1
+    pub(crate) clock: (u8, u8),
+    /// Phase 1 and phase 2 of the pre-charge period in display clocks
+    pub(crate) precharge: (u8, u8),
+    pub(crate) vcomh: VcomhLevel,
+    pub(crate) contrast: u8,
+    /// Multiplex ratio overriding the one of the display size
+    pub(crate) multiplex: Option<u8>,
+    /// Display offset in rows overriding the one of the display size
+    pub(crate) display_offset: Option<u8>,
+    pub(crate) start_line: u8,
+    pub(crate) wiring: Wiring,
+    /// Raw command bytes sent in addition to or instead of the built-in sequence
+    pub(crate) sequence: Option<InitSequence>,
+}
+
+/// A user-provided sequence of raw command bytes sent by `init`, for panels which need commands
+/// not covered by the [Builder](crate::Builder) options
+///
+/// `init` fails with `Error::InvalidParameter` without sending anything if the sequence contains
+/// an opcode unknown to the SSD1309 or ends in the middle of a command.
+///
+/// ```rust
+///# #[path = "test_helpers.rs"]
+///# mod test_helpers;
+///# use test_helpers::StubInterface;
+///# let interface = StubInterface;
+/// use ssd1309::prelude::*;
+///
+/// // Turn off, no segment remap, normal COM scan direction, contrast 0x40, turn on
+/// let sequence = InitSequence::Replace(&[0xAE, 0xA0, 0xC0, 0x81, 0x40, 0xAF]);
+/// let mut properties = ssd1309::Builder::new()
+///     .with_init_sequence(sequence)
+///     .connect(interface)
+///     .release();
+///
+/// properties.init_column_mode().unwrap();
+/// assert!(matches!(properties.get_rotation(), DisplayRotation::Rotate180));
+/// assert_eq!(properties.get_contrast(), 0x40);
+/// ```
+#[derive(Clone, Copy)]
+pub enum InitSequence {
+    /// Send the sequence instead of the built-in init sequence. The display is not turned on
+    /// unless the sequence does so.
+    ///
+    /// [GraphicsMode::init] switches to horizontal addressing mode after the sequence, as the
+    /// display buffer is sent in that mode. Only `init_column_mode` of [DisplayProperties] keeps
+    /// an addressing mode set by the sequence.
+    ///
+    /// [GraphicsMode::init]: crate::mode::GraphicsMode::init
+    /// [DisplayProperties]: crate::properties::DisplayProperties
+    Replace(&'static [u8]),
+    /// Send the sequence after the built-in init sequence
+    Append(&'static [u8]),
+}
+
+impl InitSequence {
+    /// Get the raw command bytes of the sequence
+    pub(crate) fn commands(self) -> &'static [u8] {
+        match self {
+            InitSequence::Replace(commands) | InitSequence::Append(commands) => commands,
+        }
+    }
+
+    /// Check that the sequence only consists of complete commands known to the controller
+    pub(crate) fn is_valid(self) -> bool {
+        let mut commands = self.commands();
+
+        while let Some(&opcode) = commands.first() {
+            match Command::len_of(opcode) {
+                Some(len) if len <= commands.len() => commands = &commands[len..],
+                _ => return false,
+            }
+        }
+
+        true
+    }
+}
+
+impl Default for InitConfig {
+    fn default() -> Self {
+        InitConfig {
+            clock: (0xa, 0x0),
+            precharge: (0x3, 0xd),
+            vcomh: VcomhLevel::V078,
+            contrast: 0x6f,
+            multiplex: None,
+            display_offset: None,
+            start_line: 0,
+            wiring: Wiring::new(),
+            sequence: None,
+        }
+    }
+}
+
+impl InitConfig {
+    /// Check the parameters against the ranges given in the datasheet
+    pub(crate) fn is_valid(&self) -> bool {
+        let (fosc, div) = self.clock;
+        let (phase1, phase2) = self.precharge;
+
+        fosc <= 0xf
+            && div <= 0xf
+            && (1..=0xf).contains(&phase1)
+            && (1..=0xf).contains(&phase2)
+            && self.multiplex.is_none_or(|mux| (16..=64).contains(&mux))
+            && self.display_offset.is_none_or(|offset| offset <= 63)
+            && self.start_line <= 63
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InitConfig;
+
+    #[test]
+    fn validates_parameter_ranges() {
+        fn valid(change: impl FnOnce(&mut InitConfig)) -> bool {
+            let mut config = InitConfig::default();
+            change(&mut config);
+
+            config.is_valid()
+        }
+
+        assert!(valid(|_| {}));
+
+        assert!(valid(|config| config.clock = (0, 0)));
+        assert!(valid(|config| config.clock = (0xf, 0xf)));
+        assert!(!valid(|config| config.clock = (0x10, 0)));
+        assert!(!valid(|config| config.clock = (0, 0x10)));
+
+        assert!(valid(|config| config.precharge = (1, 1)));
+        assert!(valid(|config| config.precharge = (0xf, 0xf)));
+        assert!(!valid(|config| config.precharge = (0, 1)));
+        assert!(!valid(|config| config.precharge = (1, 0)));
+        assert!(!valid(|config| config.precharge = (0x10, 1)));
+        assert!(!valid(|config| config.precharge = (1, 0x10)));
+
+        assert!(valid(|config| config.multiplex = Some(16)));
+        assert!(valid(|config| config.multiplex = Some(64)));
+        assert!(!valid(|config| config.multiplex = Some(15)));
+        assert!(!valid(|config| config.multiplex = Some(65)));
+
+        assert!(valid(|config| config.display_offset = Some(0)));
+        assert!(valid(|config| config.display_offset = Some(63)));
+        assert!(!valid(|config| config.display_offset = Some(64)));
+
+        assert!(valid(|config| config.start_line = 0));
+        assert!(valid(|config| config.start_line = 63));
+        assert!(!valid(|config| config.start_line = 64));
+    }
+}
@@ -1,24 +1,319 @@
 //! Display size
 
-/// Display size enumeration
+/// Geometry of a panel, as used by the driver at runtime. The geometry is selected using one of the
+/// [PanelSize] types.
 #[derive(Clone, Copy)]
 pub enum DisplaySize {
     /// 128 by 64 pixels
     Display128x64,
+    /// 128 by 32 pixels
+    Display128x32,
+    /// 96 by 16 pixels
+    Display96x16,
+    /// 64 by 48 pixels
+    Display64x48,
+    /// 72 by 40 pixels
+    Display72x40,
+    /// User-defined panel geometry
+    Custom(CustomSize),
 }
 
 impl DisplaySize {
     /// Get integral dimensions from DisplaySize
-    pub fn dimensions(self) -> (u8, u8) {
+    pub const fn dimensions(self) -> (u8, u8) {
         match self {
             DisplaySize::Display128x64 => (128, 64),
+            DisplaySize::Display128x32 => (128, 32),
+            DisplaySize::Display96x16 => (96, 16),
+            DisplaySize::Display64x48 => (64, 48),
+            DisplaySize::Display72x40 => (72, 40),
+            DisplaySize::Custom(size) => (size.width, size.height),
         }
     }
 
-    /// Get the panel column offset from DisplaySize
+    /// Get the number of display RAM pages covered by the rows of the display
+    pub const fn pages(self) -> u8 {
+        let (_, height) = self.dimensions();
+
+        height.div_ceil(8)
+    }
+
+    /// Get the number of bytes needed to buffer the whole display, e.g. to size the buffer of
+    /// [GraphicsMode](crate::mode::GraphicsMode)
+    pub const fn buffer_size(self) -> usize {
+        let (width, _) = self.dimensions();
+
+        width as usize * self.pages() as usize
+    }
+
+    /// Get the panel column offset from DisplaySize. The offset applies to the segment remap of the
+    /// panel wiring, see [DisplayProperties::column_offset].
+    ///
+    /// [DisplayProperties::column_offset]: crate::properties::DisplayProperties::column_offset
     pub fn column_offset(self) -> u8 {
         match self {
-            DisplaySize::Display128x64 => 0,
+            DisplaySize::Display128x64 | DisplaySize::Display128x32 | DisplaySize::Display96x16 => {
+                0
+            }
+            DisplaySize::Display64x48 => 32,
+            DisplaySize::Display72x40 => 28,
+            DisplaySize::Custom(size) => size.column_offset,
+        }
+    }
+
+    /// Get the vertical display offset in rows from DisplaySize
+    pub fn row_offset(self) -> u8 {
+        match self {
+            DisplaySize::Display128x64
+            | DisplaySize::Display128x32
+            | DisplaySize::Display96x16
+            | DisplaySize::Display64x48
+            | DisplaySize::Display72x40 => 0,
+            DisplaySize::Custom(size) => size.row_offset,
+        }
+    }
+
+    /// Get the multiplex ratio, i.e. the number of driven COM lines, from DisplaySize
+    pub fn multiplex(self) -> u8 {
+        match self {
+            DisplaySize::Custom(size) => size.multiplex,
+            _ => self.dimensions().1,
+        }
+    }
+
+    /// Get whether the panel uses the alternative (`true`) or sequential (`false`) COM pin
+    /// configuration from DisplaySize
+    pub fn com_pin_alternative(self) -> bool {
+        match self {
+            DisplaySize::Display128x64 | DisplaySize::Display64x48 | DisplaySize::Display72x40 => {
+                true
+            }
+            DisplaySize::Display128x32 | DisplaySize::Display96x16 => false,
+            DisplaySize::Custom(size) => size.com_pin_alternative,
+        }
+    }
+
+    /// Check whether the geometry can be driven by the controller, which has 128 segments and
+    /// between 16 and 64 COM lines. The display must fit within the segments after applying the
+    /// column offset, and within the multiplex ratio.
+    pub fn is_valid(self) -> bool {
+        let (width, height) = self.dimensions();
+        let multiplex = self.multiplex();
+
+        width > 0
+            && height > 0
+            && width as u16 + self.column_offset() as u16 <= 128
+            && (16..=64).contains(&multiplex)
+            && height <= multiplex
+            && self.row_offset() < 64
+    }
+}
+
+/// Geometry of a panel that isn't covered by the predefined sizes
+///
+/// As the geometry is only known at runtime, the display buffer of a
+/// [GraphicsMode](crate::mode::GraphicsMode) using it holds 1024 bytes by default, and its size
+/// is checked by `init` and the flush methods.
+///
+/// ```rust
+///# #[path = "test_helpers.rs"]
+///# mod test_helpers;
+///# use test_helpers::StubInterface;
+///# let interface = StubInterface;
+/// use ssd1309::prelude::*;
+///
+/// let size = CustomSize::new(100, 20)
+///     .with_column_offset(14)
+///     .with_multiplex(24)
+///     .with_com_pin_alternative(false);
+///
+/// assert!(size.display_size().is_valid());
+/// assert_eq!(size.display_size().pages(), 3);
+///
+/// let mut display: GraphicsMode<_, CustomSize> =
+///     ssd1309::Builder::new().with_size(size).connect(interface).into();
+/// display.init().unwrap();
+/// ```
+#[derive(Clone, Copy)]
+pub struct CustomSize {
+    width: u8,
+    height: u8,
+    column_offset: u8,
+    row_offset: u8,
+    multiplex: u8,
+    com_pin_alternative: bool,
+}
+
+impl CustomSize {
+    /// Create a new geometry of `width` by `height` pixels without offsets, a multiplex ratio
+    /// matching the height and the alternative COM pin configuration.
+    pub const fn new(width: u8, height: u8) -> Self {
+        CustomSize {
+            width,
+            height,
+            column_offset: 0,
+            row_offset: 0,
+            multiplex: height,
+            com_pin_alternative: true,
+        }
+    }
+
+    /// Set the first display RAM column connected to the panel, given for the segment remap of
+    /// the panel wiring
+    pub const fn with_column_offset(self, column_offset: u8) -> Self {
+        Self {
+            column_offset,
+            ..self
+        }
+    }
+
+    /// Set the vertical display offset in rows
+    pub const fn with_row_offset(self, row_offset: u8) -> Self {
+        Self { row_offset, ..self }
+    }
+
+    /// Set the multiplex ratio, i.e. the number of driven COM lines, from 16 to 64
+    pub const fn with_multiplex(self, multiplex: u8) -> Self {
+        Self { multiplex, ..self }
+    }
+
+    /// Set whether the panel uses the alternative (`true`) or sequential (`false`) COM pin
+    /// configuration
+    pub const fn with_com_pin_alternative(self, com_pin_alternative: bool) -> Self {
+        Self {
+            com_pin_alternative,
+            ..self
         }
     }
 }
+
+/// Panel geometry as part of the driver type, selected using
+/// [Builder::with_size](crate::Builder::with_size). The display buffer of a
+/// [GraphicsMode](crate::mode::GraphicsMode) is sized to the panel by default, and an inline
+/// array of another size fails to compile:
+///
+/// ```rust
+///# #[path = "test_helpers.rs"]
+///# mod test_helpers;
+///# use test_helpers::StubInterface;
+///# let interface = StubInterface;
+/// use ssd1309::prelude::*;
+///
+/// let mut display: GraphicsMode<_, DisplaySize64x48> = ssd1309::Builder::new()
+///     .with_size(DisplaySize64x48)
+///     .connect(interface)
+///     .into();
+///
+/// assert_eq!(core::mem::size_of_val(&display.release_buffer().1), 384);
+/// ```
+///
+/// ```rust,compile_fail
+///# #[path = "test_helpers.rs"]
+///# mod test_helpers;
+///# use test_helpers::StubInterface;
+///# let interface = StubInterface;
+/// use ssd1309::prelude::*;
+///
+/// let display: GraphicsMode<_, DisplaySize64x48, [u8; 1024]> = ssd1309::Builder::new()
+///     .with_size(DisplaySize64x48)
+///     .connect(interface)
+///     .into();
+/// ```
+pub trait PanelSize: Copy {
+    /// Width in pixels, or the largest width for a [CustomSize]
+    const WIDTH: u8;
+    /// Height in pixels, or the largest height for a [CustomSize]
+    const HEIGHT: u8;
+    /// Number of bytes needed to buffer the whole display
+    const BUFFER_SIZE: usize = Self::WIDTH as usize * (Self::HEIGHT as usize).div_ceil(8);
+    /// Number of bytes an inline display buffer must hold at least. Only a [CustomSize] accepts
+    /// smaller buffers than `BUFFER_SIZE`, as its geometry is checked at runtime.
+    const MIN_BUFFER_SIZE: usize = Self::BUFFER_SIZE;
+
+    /// Inline array used as display buffer by default
+    type Buffer: AsMut<[u8]> + AsRef<[u8]>;
+
+    /// Get the geometry used by the driver at runtime
+    fn display_size(self) -> DisplaySize;
+}
+
+/// 128 by 64 pixels
+#[derive(Clone, Copy)]
+pub struct DisplaySize128x64;
+
+impl PanelSize for DisplaySize128x64 {
+    const WIDTH: u8 = 128;
+    const HEIGHT: u8 = 64;
+    type Buffer = [u8; Self::BUFFER_SIZE];
+
+    fn display_size(self) -> DisplaySize {
+        DisplaySize::Display128x64
+    }
+}
+
+/// 128 by 32 pixels
+#[derive(Clone, Copy)]
+pub struct DisplaySize128x32;
+
+impl PanelSize for DisplaySize128x32 {
+    const WIDTH: u8 = 128;
+    const HEIGHT: u8 = 32;
+    type Buffer = [u8; Self::BUFFER_SIZE];
+
+    fn display_size(self) -> DisplaySize {
+        DisplaySize::Display128x32
+    }
+}
+
+/// 96 by 16 pixels
+#[derive(Clone, Copy)]
+pub struct DisplaySize96x16;
+
+impl PanelSize for DisplaySize96x16 {
+    const WIDTH: u8 = 96;
+    const HEIGHT: u8 = 16;
+    type Buffer = [u8; Self::BUFFER_SIZE];
+
+    fn display_size(self) -> DisplaySize {
+        DisplaySize::Display96x16
+    }
+}
+
+/// 64 by 48 pixels
+#[derive(Clone, Copy)]
+pub struct DisplaySize64x48;
+
+impl PanelSize for DisplaySize64x48 {
+    const WIDTH: u8 = 64;
+    const HEIGHT: u8 = 48;
+    type Buffer = [u8; Self::BUFFER_SIZE];
+
+    fn display_size(self) -> DisplaySize {
+        DisplaySize::Display64x48
+    }
+}
+
+/// 72 by 40 pixels
+#[derive(Clone, Copy)]
+pub struct DisplaySize72x40;
+
+impl PanelSize for DisplaySize72x40 {
+    const WIDTH: u8 = 72;
+    const HEIGHT: u8 = 40;
+    type Buffer = [u8; Self::BUFFER_SIZE];
+
+    fn display_size(self) -> DisplaySize {
+        DisplaySize::Display72x40
+    }
+}
+
+impl PanelSize for CustomSize {
+    const WIDTH: u8 = 128;
+    const HEIGHT: u8 = 64;
+    const MIN_BUFFER_SIZE: usize = 1;
+    type Buffer = [u8; Self::BUFFER_SIZE];
+
+    fn display_size(self) -> DisplaySize {
+        DisplaySize::Custom(self)
+    }
+}
@@ -0,0 +1,93 @@
+//! Driver errors
+
+use core::{convert::Infallible, fmt};
+
+use display_interface::DisplayError;
+
+/// Operation of the driver which failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    /// Initialising the display
+    Init,
+    /// Resetting the display
+    Reset,
+    /// Setting the draw area
+    SetDrawArea,
+    /// Drawing to the display RAM
+    Draw,
+    /// Flushing the display buffer
+    Flush,
+    /// Setting the addressing mode
+    SetAddrMode,
+    /// Setting the rotation or mirroring
+    SetOrientation,
+    /// Turning the display on or off
+    DisplayOn,
+    /// Setting the contrast
+    SetContrast,
+    /// Enabling or disabling zoom in mode
+    SetZoom,
+    /// Setting the fade out or blinking mode
+    SetFade,
+    /// Configuring a scroll
+    ScrollSetup,
+    /// Starting a scroll
+    StartScroll,
+    /// Stopping a scroll
+    StopScroll,
+    /// Scrolling the display content by one column
+    ScrollContent,
+    /// Enabling or disabling the command lock
+    SetCommandLock,
+    /// Sending a low-level command
+    Command,
+}
+
+/// Errors of the driver
+///
+/// `PinE` is the error type of the reset pin, which is only used by `reset`.
+#[derive(Debug)]
+pub enum Error<PinE = Infallible> {
+    /// Communication with the display failed during the operation
+    Interface(Operation, DisplayError),
+    /// Setting the reset pin failed
+    Pin(PinE),
+    /// A parameter of the operation, or the configuration of the driver, is out of range
+    InvalidParameter(Operation),
+}
+
+impl<PinE> Error<PinE> {
+    /// Get the operation which failed
+    pub fn operation(&self) -> Operation {
+        match self {
+            Error::Interface(operation, _) | Error::InvalidParameter(operation) => *operation,
+            Error::Pin(_) => Operation::Reset,
+        }
+    }
+}
+
+impl Error<Infallible> {
+    /// Convert into an error with any reset pin error type, e.g. to combine the result of `init`
+    /// and `reset` using `?`
+    pub fn with_pin_error<PinE>(self) -> Error<PinE> {
+        match self {
+            Error::Interface(operation, error) => Error::Interface(operation, error),
+            Error::Pin(never) => match never {},
+            Error::InvalidParameter(operation) => Error::InvalidParameter(operation),
+        }
+    }
+}
+
+impl<PinE: fmt::Debug> fmt::Display for Error<PinE> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Interface(operation, error) => {
+                write!(f, "interface error during {:?}: {:?}", operation, error)
+            }
+            Error::Pin(error) => write!(f, "reset pin error: {:?}", error),
+            Error::InvalidParameter(operation) => {
+                write!(f, "invalid parameter for {:?}", operation)
+            }
+        }
+    }
+}
@@ -0,0 +1,69 @@
+//! Support for embedded-hal 1.0, available with the `embedded-hal-1` feature
+//!
+//! The reset methods of the driver take a pin and a delay implementing the embedded-hal 0.2
+//! traits. Wrap a pin implementing the embedded-hal 1.0 `OutputPin` trait and a delay
+//! implementing `DelayNs` in [Hal1] to pass them instead:
+//!
+//! ```rust,ignore
+//! use ssd1309::hal1::Hal1;
+//!
+//! display.reset(&mut Hal1(rst), &mut Hal1(delay)).unwrap();
+//! ```
+//!
+//! [NoOutputPin](crate::NoOutputPin) implements the `OutputPin` traits of both versions.
+
+use embedded_hal_1::{delay::DelayNs, digital};
+use hal::{blocking::delay::DelayMs, digital::v2::OutputPin};
+
+/// Adapter implementing the embedded-hal 0.2 traits used by the driver for a pin or delay which
+/// implements the embedded-hal 1.0 traits
+#[derive(Debug, Clone, Copy)]
+pub struct Hal1<T>(pub T);
+
+impl<PIN> OutputPin for Hal1<PIN>
+where
+    PIN: digital::OutputPin,
+{
+    type Error = PIN::Error;
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.0.set_low()
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.0.set_high()
+    }
+}
+
+impl<DELAY> DelayMs<u8> for Hal1<DELAY>
+where
+    DELAY: DelayNs,
+{
+    fn delay_ms(&mut self, ms: u8) {
+        self.0.delay_ms(ms.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::convert::Infallible;
+
+    use super::Hal1;
+    use crate::{
+        builder::{Builder, NoOutputPin},
+        mode::GraphicsMode,
+        test_helpers::{DelayStub, PinStub, StubInterface},
+    };
+
+    #[test]
+    fn reset_with_hal1_traits() {
+        let mut display: GraphicsMode<_> = Builder::new().connect(StubInterface).into();
+
+        display
+            .reset(&mut Hal1(PinStub), &mut Hal1(DelayStub))
+            .unwrap();
+        display
+            .reset(&mut Hal1(NoOutputPin::<Infallible>::new()), &mut DelayStub)
+            .unwrap();
+    }
+}
@@ -25,6 +25,17 @@
 //! It's possible to customise the driver to suit your display/application. Take a look at the
 //! [Builder] for available options.
 //!
+//! Fallible methods of the driver return an [Error], which records the [Operation] that failed.
+//! Commands the driver doesn't wrap can be sent using the low-level [command] module.
+//!
+//! Optional features:
+//!
+//! - `graphics` (enabled by default): `embedded-graphics` support for `mode::GraphicsMode`
+//! - `async`: `mode::AsyncGraphicsMode` for async `display-interface` implementors, reset using
+//!   pins implementing the embedded-hal 1.0 traits. Enables `embedded-hal-1`.
+//! - `embedded-hal-1`: the `hal1` module to reset the display using pins and delays
+//!   implementing the embedded-hal 1.0 traits
+//!
 //! # Examples
 //!
 //! Examples can be found in
@@ -93,7 +104,7 @@
 //!     disp.reset(&mut res, &mut delay).unwrap();
 //!     disp.init().unwrap();
 //!     disp.flush().unwrap();
-//! 
+//!
 //!     let style = MonoTextStyle::new(&FONT_5X8, BinaryColor::On);
 //!
 //!     Text::new("Hello world!", Point::new(0, 0), style).draw(&mut disp).unwrap();
@@ -115,11 +126,25 @@
 extern crate embedded_hal as hal;
 
 pub mod builder;
-mod command;
+pub mod command;
+mod config;
 pub mod displayrotation;
 mod displaysize;
+pub mod error;
+#[cfg(feature = "embedded-hal-1")]
+pub mod hal1;
 pub mod mode;
 pub mod prelude;
 pub mod properties;
+pub mod ticker;
+mod wiring;
+
+#[cfg(test)]
+mod test_helpers;
 
-pub use crate::builder::{Builder, NoOutputPin};
+pub use crate::{
+    builder::{Builder, NoOutputPin},
+    command::{AddrMode, Command, FadeMode, GpioMode, NFrames, Page, ScrollDirection, VcomhLevel},
+    config::InitSequence,
+    error::{Error, Operation},
+};
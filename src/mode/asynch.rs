@@ -0,0 +1,762 @@
+//! Buffered display module for async interfaces, available with the `async` feature
+//!
+//! [AsyncGraphicsMode] offers the methods of [GraphicsMode] as async methods built on the
+//! [AsyncWriteOnlyDataCommand] trait of `display-interface` 0.5, so that flushing the display
+//! doesn't block the executor:
+//!
+//! ```rust,ignore
+//! let interface = /* your preferred async `display-interface` implementor */;
+//! let mut display: AsyncGraphicsMode<_> = Builder::new().connect_async(interface);
+//!
+//! display.reset(&mut rst, &mut delay).await.unwrap();
+//! display.init().await.unwrap();
+//! Text::new("Hello Rust!", Point::new(0, 16), style).draw(&mut display).unwrap();
+//! display.flush().await.unwrap();
+//! ```
+//!
+//! The commands of each operation are generated by the same code as for the blocking driver and
+//! queued, then sent to the interface. Data is sent directly from the display buffer.
+
+use core::ops::Range;
+
+use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+use display_interface_async::{
+    AsyncWriteOnlyDataCommand, DataFormat as AsyncDataFormat, DisplayError as AsyncDisplayError,
+};
+use embedded_hal_1::digital::OutputPin;
+use embedded_hal_async::delay::DelayNs;
+
+use crate::{
+    command::{FadeMode, NFrames, Page, ScrollDirection},
+    displayrotation::DisplayRotation,
+    displaysize::{DisplaySize128x64, PanelSize},
+    error::{Error, Operation},
+    mode::graphics::{GraphicsMode, SavedState},
+    ticker::Ticker,
+};
+
+/// Number of command bytes the queue holds, enough for the built-in init sequence
+const QUEUE_BYTES: usize = 64;
+/// Number of transfers the queue holds, enough to draw all pages in page addressing mode
+const QUEUE_SEGMENTS: usize = 20;
+
+/// Transfer recorded by a [CommandQueue]
+#[derive(Clone, Copy)]
+enum Segment {
+    /// Range of the queued command bytes
+    Commands(u8, u8),
+    /// Custom sequence of raw command bytes
+    Sequence(&'static [u8]),
+    /// Number of data bytes taken from the data being drawn
+    Data(usize),
+}
+
+/// Interface which queues the commands sent by the blocking driver code until they are sent to
+/// the async interface. Data is only recorded by its length, as it is taken from the display
+/// buffer when the queue is sent.
+#[derive(Clone, Copy)]
+pub(crate) struct CommandQueue {
+    bytes: [u8; QUEUE_BYTES],
+    byte_count: usize,
+    segments: [Segment; QUEUE_SEGMENTS],
+    segment_count: usize,
+}
+
+impl CommandQueue {
+    pub(crate) fn new() -> Self {
+        CommandQueue {
+            bytes: [0; QUEUE_BYTES],
+            byte_count: 0,
+            segments: [Segment::Data(0); QUEUE_SEGMENTS],
+            segment_count: 0,
+        }
+    }
+
+    /// Take the queued transfers, leaving the queue empty
+    fn take(&mut self) -> Self {
+        core::mem::replace(self, CommandQueue::new())
+    }
+
+    fn push(&mut self, segment: Segment) -> Result<(), DisplayError> {
+        let slot = self
+            .segments
+            .get_mut(self.segment_count)
+            .ok_or(DisplayError::OutOfBoundsError)?;
+        *slot = segment;
+        self.segment_count += 1;
+
+        Ok(())
+    }
+
+    /// Queue a custom sequence of raw command bytes without copying it
+    pub(crate) fn push_sequence(&mut self, sequence: &'static [u8]) -> Result<(), DisplayError> {
+        self.push(Segment::Sequence(sequence))
+    }
+
+    /// Send the queued transfers to `iface`, taking the data from `data`
+    async fn send<DI>(&self, iface: &mut DI, mut data: &[u8]) -> Result<(), AsyncDisplayError>
+    where
+        DI: AsyncWriteOnlyDataCommand,
+    {
+        for segment in &self.segments[..self.segment_count] {
+            match *segment {
+                Segment::Commands(start, end) => {
+                    let commands = &self.bytes[start as usize..end as usize];
+                    iface.send_commands(AsyncDataFormat::U8(commands)).await?;
+                }
+                Segment::Sequence(sequence) => {
+                    iface.send_commands(AsyncDataFormat::U8(sequence)).await?;
+                }
+                Segment::Data(len) => {
+                    let (chunk, rest) = data.split_at(len.min(data.len()));
+                    iface.send_data(AsyncDataFormat::U8(chunk)).await?;
+                    data = rest;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl WriteOnlyDataCommand for CommandQueue {
+    fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+        let commands = match cmd {
+            DataFormat::U8(commands) => commands,
+            _ => return Err(DisplayError::DataFormatNotImplemented),
+        };
+
+        let start = self.byte_count;
+        let end = start + commands.len();
+        self.bytes
+            .get_mut(start..end)
+            .ok_or(DisplayError::OutOfBoundsError)?
+            .copy_from_slice(commands);
+        self.byte_count = end;
+
+        // Consecutive commands are sent in a single transfer
+        match self
+            .segment_count
+            .checked_sub(1)
+            .map(|last| &mut self.segments[last])
+        {
+            Some(Segment::Commands(_, last_end)) if *last_end as usize == start => {
+                *last_end = end as u8;
+                Ok(())
+            }
+            _ => self.push(Segment::Commands(start as u8, end as u8)),
+        }
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        match buf {
+            DataFormat::U8(data) => self.push(Segment::Data(data.len())),
+            _ => Err(DisplayError::DataFormatNotImplemented),
+        }
+    }
+}
+
+/// Convert an error of the async interface into the error type used by the driver
+fn convert_error(error: AsyncDisplayError) -> DisplayError {
+    match error {
+        AsyncDisplayError::InvalidFormatError => DisplayError::InvalidFormatError,
+        AsyncDisplayError::DCError => DisplayError::DCError,
+        AsyncDisplayError::CSError => DisplayError::CSError,
+        AsyncDisplayError::DataFormatNotImplemented => DisplayError::DataFormatNotImplemented,
+        AsyncDisplayError::RSError => DisplayError::RSError,
+        AsyncDisplayError::OutOfBoundsError => DisplayError::OutOfBoundsError,
+        _ => DisplayError::BusWriteError,
+    }
+}
+
+/// Source of the data sent by an operation
+enum Data<'a> {
+    /// Range of the display buffer
+    Buffer(Range<usize>),
+    /// Bytes which aren't part of the display buffer
+    Bytes(&'a [u8]),
+}
+
+/// Restores the state of the blocking driver when dropped while armed, so that state changes
+/// only take effect once their commands have been sent
+struct StateGuard<'a, SIZE, BUF>
+where
+    SIZE: PanelSize,
+    BUF: AsMut<[u8]> + AsRef<[u8]>,
+{
+    mode: &'a mut GraphicsMode<CommandQueue, SIZE, BUF>,
+    state: Option<SavedState<CommandQueue, SIZE>>,
+}
+
+impl<SIZE, BUF> Drop for StateGuard<'_, SIZE, BUF>
+where
+    SIZE: PanelSize,
+    BUF: AsMut<[u8]> + AsRef<[u8]>,
+{
+    fn drop(&mut self) {
+        if let Some(state) = self.state.take() {
+            self.mode.restore_state(state);
+        }
+    }
+}
+
+/// Async graphics mode handler
+///
+/// Drawing to the display buffer works the same as with [GraphicsMode]. All methods which
+/// communicate with the display are async. Create an instance using
+/// [Builder::connect_async](crate::Builder::connect_async).
+///
+/// Not every method of [GraphicsMode] has an async counterpart:
+///
+/// - There is no `begin_flush`, as `flush` already yields to the executor while each transfer is
+///   sent. Dropping the future of a `flush` stops it, and the parts of the buffer which weren't
+///   sent yet are sent by the next `flush`.
+/// - [GraphicsMode] doesn't offer `set_addr_mode` either: both modes switch to horizontal
+///   addressing in `init` and depend on it to flush the buffer.
+pub struct AsyncGraphicsMode<DI, SIZE = DisplaySize128x64, BUF = <SIZE as PanelSize>::Buffer>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    SIZE: PanelSize,
+    BUF: AsMut<[u8]> + AsRef<[u8]>,
+{
+    mode: GraphicsMode<CommandQueue, SIZE, BUF>,
+    iface: DI,
+}
+
+impl<DI, SIZE, BUF> AsyncGraphicsMode<DI, SIZE, BUF>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    SIZE: PanelSize,
+    BUF: AsMut<[u8]> + AsRef<[u8]>,
+{
+    /// Create a new AsyncGraphicsMode instance sending the commands queued by `mode` to `iface`
+    pub(crate) fn new(mode: GraphicsMode<CommandQueue, SIZE, BUF>, iface: DI) -> Self {
+        AsyncGraphicsMode { mode, iface }
+    }
+
+    /// Release the interface and the display buffer
+    pub fn release(self) -> (DI, BUF) {
+        let (_, buffer) = self.mode.release_buffer();
+
+        (self.iface, buffer)
+    }
+
+    /// Run `f` on the blocking driver, then send the queued commands and the `data` range of the
+    /// display buffer as `operation`. The state changed by `f` is reverted if it fails, if sending
+    /// fails or if the returned future is dropped before sending completes.
+    async fn run<F>(&mut self, operation: Operation, data: Range<usize>, f: F) -> Result<(), Error>
+    where
+        F: FnOnce(&mut GraphicsMode<CommandQueue, SIZE, BUF>) -> Result<(), Error>,
+    {
+        self.run_with(operation, Data::Buffer(data), f).await
+    }
+
+    /// Same as `run`, taking the sent data from `data`
+    async fn run_with<F>(&mut self, operation: Operation, data: Data<'_>, f: F) -> Result<(), Error>
+    where
+        F: FnOnce(&mut GraphicsMode<CommandQueue, SIZE, BUF>) -> Result<(), Error>,
+    {
+        let state = self.mode.save_state();
+        let mut guard = StateGuard {
+            mode: &mut self.mode,
+            state: Some(state),
+        };
+
+        let result = f(guard.mode);
+        let queue = guard.mode.properties_mut().iface_mut().take();
+        result?;
+
+        let data = match data {
+            Data::Buffer(range) => &guard.mode.buffer()[range],
+            Data::Bytes(bytes) => bytes,
+        };
+
+        queue
+            .send(&mut self.iface, data)
+            .await
+            .map_err(|error| Error::Interface(operation, convert_error(error)))?;
+        guard.state = None;
+
+        Ok(())
+    }
+
+    /// Clear the display buffer. You need to call `flush` for any effect on the screen.
+    pub fn clear(&mut self) {
+        self.mode.clear()
+    }
+
+    /// Reset display. This is very important on the SSD1309!
+    ///
+    /// This should be called before `init` or any other methods.
+    pub async fn reset<RST, DELAY, PinE>(
+        &mut self,
+        rst: &mut RST,
+        delay: &mut DELAY,
+    ) -> Result<(), Error<PinE>>
+    where
+        RST: OutputPin<Error = PinE>,
+        DELAY: DelayNs,
+    {
+        rst.set_high().map_err(Error::Pin)?;
+        delay.delay_ms(10).await;
+        rst.set_low().map_err(Error::Pin)?;
+        delay.delay_ms(10).await;
+        rst.set_high().map_err(Error::Pin)?;
+        delay.delay_ms(10).await;
+        Ok(())
+    }
+
+    /// Initialise the display. See [GraphicsMode::init].
+    pub async fn init(&mut self) -> Result<(), Error> {
+        self.run(Operation::Init, 0..0, |mode| {
+            mode.init_with(|queue, sequence| queue.push_sequence(sequence))
+        })
+        .await
+    }
+
+    /// Write out the parts of the buffer that changed since the last flush to the display. See
+    /// [GraphicsMode::flush].
+    pub async fn flush(&mut self) -> Result<(), Error> {
+        self.mode.check_geometry(Operation::Flush)?;
+
+        if self.mode.next_dirty_window().is_none() {
+            return Ok(());
+        }
+
+        self.mode.resend_if_scrolled();
+
+        let scrolling = self.mode.properties().is_scrolling();
+        let mut flushed = false;
+
+        while let Some((columns, pages)) = self.mode.next_dirty_window() {
+            self.flush_window(columns, pages).await?;
+            self.mode.clear_dirty(columns, pages);
+            flushed = true;
+        }
+
+        if flushed && scrolling {
+            self.start_scroll().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Send a window of columns and pages to the display
+    async fn flush_window(
+        &mut self,
+        columns: (u8, u8),
+        pages: (usize, usize),
+    ) -> Result<(), Error> {
+        let (start, end) = self.mode.window_area(columns, pages);
+        self.run(Operation::SetDrawArea, 0..0, |mode| {
+            mode.properties_mut().set_draw_area(start, end)
+        })
+        .await?;
+
+        for range in self.mode.window_ranges(columns, pages) {
+            self.run(Operation::Draw, range.clone(), |mode| {
+                mode.draw_range(range)
+            })
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Write out the whole buffer to the display, regardless of which parts changed. See
+    /// [GraphicsMode::flush_all].
+    pub async fn flush_all(&mut self) -> Result<(), Error> {
+        self.mode.check_geometry(Operation::Flush)?;
+
+        let scrolling = self.mode.properties().is_scrolling();
+        let (start, end, length) = self.mode.frame_area();
+
+        self.run(Operation::SetDrawArea, 0..0, |mode| {
+            mode.properties_mut().set_draw_area(start, end)
+        })
+        .await?;
+        self.run(Operation::Draw, 0..length, |mode| {
+            mode.draw_range(0..length)
+        })
+        .await?;
+        self.mode.clear_all_dirty(end.1 as usize / 8);
+
+        if scrolling {
+            self.start_scroll().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Turn a pixel on or off. See [GraphicsMode::set_pixel].
+    pub fn set_pixel(&mut self, x: u32, y: u32, value: u8) {
+        self.mode.set_pixel(x, y, value)
+    }
+
+    /// Get display dimensions, taking into account the current rotation of the display
+    pub fn get_dimensions(&self) -> (u8, u8) {
+        self.mode.get_dimensions()
+    }
+
+    /// Set the display rotation. The whole buffer is sent by the next flush.
+    pub async fn set_rotation(&mut self, rot: DisplayRotation) -> Result<(), Error> {
+        self.run(Operation::SetOrientation, 0..0, |mode| {
+            mode.set_rotation(rot)
+        })
+        .await
+    }
+
+    /// Mirror the display horizontally and/or vertically. The whole buffer is sent by the next
+    /// flush.
+    pub async fn set_mirror(&mut self, horizontal: bool, vertical: bool) -> Result<(), Error> {
+        self.run(Operation::SetOrientation, 0..0, |mode| {
+            mode.set_mirror(horizontal, vertical)
+        })
+        .await
+    }
+
+    /// Turn the display on or off. The display can be drawn to and retains all
+    /// of its memory even while off.
+    pub async fn display_on(&mut self, on: bool) -> Result<(), Error> {
+        self.run(Operation::DisplayOn, 0..0, |mode| mode.display_on(on))
+            .await
+    }
+
+    /// Set the display contrast
+    pub async fn set_contrast(&mut self, contrast: u8) -> Result<(), Error> {
+        self.run(Operation::SetContrast, 0..0, |mode| {
+            mode.set_contrast(contrast)
+        })
+        .await
+    }
+
+    /// Enable or disable zoom in mode. See [GraphicsMode::set_zoom].
+    pub async fn set_zoom(&mut self, zoom: bool) -> Result<(), Error> {
+        self.run(Operation::SetZoom, 0..0, |mode| mode.set_zoom(zoom))
+            .await
+    }
+
+    /// Enable or disable the command lock. See [GraphicsMode::set_command_lock].
+    pub async fn set_command_lock(&mut self, lock: bool) -> Result<(), Error> {
+        self.run(Operation::SetCommandLock, 0..0, |mode| {
+            mode.set_command_lock(lock)
+        })
+        .await
+    }
+
+    /// Set the fade out or blinking mode. See [GraphicsMode::set_fade].
+    pub async fn set_fade(&mut self, fade: FadeMode, interval: u8) -> Result<(), Error> {
+        self.run(Operation::SetFade, 0..0, |mode| {
+            mode.set_fade(fade, interval)
+        })
+        .await
+    }
+
+    /// Configure a continuous horizontal scroll. See [GraphicsMode::set_horizontal_scroll].
+    pub async fn set_horizontal_scroll(
+        &mut self,
+        direction: ScrollDirection,
+        pages: (Page, Page),
+        columns: (u8, u8),
+        interval: NFrames,
+    ) -> Result<(), Error> {
+        self.run(Operation::ScrollSetup, 0..0, |mode| {
+            mode.set_horizontal_scroll(direction, pages, columns, interval)
+        })
+        .await
+    }
+
+    /// Configure a continuous vertical scroll, optionally combined with a horizontal scroll. See
+    /// [GraphicsMode::set_vertical_scroll].
+    pub async fn set_vertical_scroll(
+        &mut self,
+        horizontal: Option<ScrollDirection>,
+        pages: (Page, Page),
+        columns: (u8, u8),
+        vertical_offset: u8,
+        interval: NFrames,
+    ) -> Result<(), Error> {
+        self.run(Operation::ScrollSetup, 0..0, |mode| {
+            mode.set_vertical_scroll(horizontal, pages, columns, vertical_offset, interval)
+        })
+        .await
+    }
+
+    /// Set the area used by vertical scrolling. See [GraphicsMode::set_vertical_scroll_area].
+    /// Nothing is sent to the display until the next call to `start_scroll`.
+    pub fn set_vertical_scroll_area(
+        &mut self,
+        fixed_rows: u8,
+        scroll_rows: u8,
+    ) -> Result<(), Error> {
+        self.mode.set_vertical_scroll_area(fixed_rows, scroll_rows)
+    }
+
+    /// Start the configured scroll
+    pub async fn start_scroll(&mut self) -> Result<(), Error> {
+        self.run(Operation::StartScroll, 0..0, |mode| mode.start_scroll())
+            .await
+    }
+
+    /// Advance a [Ticker] by one column. See [GraphicsMode::step_ticker]. The ticker only moves
+    /// on once the step has been sent.
+    pub async fn step_ticker(&mut self, ticker: &mut Ticker<'_>) -> Result<(), Error> {
+        let column = ticker.next_column(ticker.source_width(self.mode.properties())?);
+        let mut stepped = *ticker;
+
+        self.run_with(Operation::ScrollContent, Data::Bytes(&column), |mode| {
+            mode.step_ticker(&mut stepped)
+        })
+        .await?;
+        *ticker = stepped;
+
+        Ok(())
+    }
+
+    /// Hand the area of the ticker last stepped by `step_ticker` back to the display buffer. See
+    /// [GraphicsMode::stop_ticker].
+    pub fn stop_ticker(&mut self) {
+        self.mode.stop_ticker()
+    }
+
+    /// Stop any active scroll. The whole buffer is sent by the next flush to restore the display
+    /// content.
+    pub async fn stop_scroll(&mut self) -> Result<(), Error> {
+        self.run(Operation::StopScroll, 0..0, |mode| mode.stop_scroll())
+            .await
+    }
+}
+
+#[cfg(feature = "graphics")]
+use embedded_graphics_core::{
+    draw_target::DrawTarget,
+    geometry::{OriginDimensions, Size},
+    pixelcolor::BinaryColor,
+    primitives::Rectangle,
+    Pixel,
+};
+
+#[cfg(feature = "graphics")]
+impl<DI, SIZE, BUF> AsyncGraphicsMode<DI, SIZE, BUF>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    SIZE: PanelSize,
+    BUF: AsMut<[u8]> + AsRef<[u8]>,
+{
+    /// Write out the part of the buffer covered by `area` to the display. See
+    /// [GraphicsMode::flush_region].
+    pub async fn flush_region(&mut self, area: Rectangle) -> Result<(), Error> {
+        self.mode.check_geometry(Operation::Flush)?;
+
+        let (columns, pages) = match self.mode.region_window(area) {
+            Some(window) => window,
+            None => return Ok(()),
+        };
+
+        let scrolling = self.mode.properties().is_scrolling();
+
+        self.mode.mark_scrolled();
+        self.flush_window(columns, pages).await?;
+
+        if scrolling {
+            self.start_scroll().await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl<DI, SIZE, BUF> DrawTarget for AsyncGraphicsMode<DI, SIZE, BUF>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    SIZE: PanelSize,
+    BUF: AsMut<[u8]> + AsRef<[u8]>,
+{
+    type Color = BinaryColor;
+    type Error = Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        self.mode.draw_iter(pixels)
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl<DI, SIZE, BUF> OriginDimensions for AsyncGraphicsMode<DI, SIZE, BUF>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    SIZE: PanelSize,
+    BUF: AsMut<[u8]> + AsRef<[u8]>,
+{
+    fn size(&self) -> Size {
+        self.mode.size()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::{
+        future::Future,
+        pin::pin,
+        task::{Context, Poll, Waker},
+    };
+
+    use display_interface_async::{AsyncWriteOnlyDataCommand, DataFormat, DisplayError};
+
+    use super::AsyncGraphicsMode;
+    use crate::{
+        builder::Builder,
+        command::{NFrames, Page, ScrollDirection},
+        config::InitSequence,
+        displayrotation::DisplayRotation,
+        mode::GraphicsMode,
+        test_helpers::RecordingInterface,
+        ticker::Ticker,
+    };
+
+    /// Interface which never completes a transfer
+    struct PendingInterface;
+
+    impl AsyncWriteOnlyDataCommand for PendingInterface {
+        async fn send_commands(&mut self, _cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+            core::future::pending().await
+        }
+
+        async fn send_data(&mut self, _buf: DataFormat<'_>) -> Result<(), DisplayError> {
+            core::future::pending().await
+        }
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = pin!(future);
+        let mut context = Context::from_waker(Waker::noop());
+
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+                return output;
+            }
+        }
+    }
+
+    #[test]
+    fn sends_same_bytes_as_blocking_driver() {
+        let builder = Builder::new().with_init_sequence(InitSequence::Append(&[0xD9, 0x22]));
+        let source = [1, 2, 3, 11, 12, 13];
+        let pages = (Page::Page2, Page::Page3);
+        let mut ticker = Ticker::new(&source, ScrollDirection::Right, pages, (10, 49));
+
+        let mut display: GraphicsMode<_> = builder.connect(RecordingInterface::new()).into();
+        display.set_command_lock(true).unwrap();
+        display.init().unwrap();
+        display.flush().unwrap();
+        display.set_pixel(3, 20, 1);
+        display.set_pixel(90, 40, 1);
+        display.flush().unwrap();
+        display.set_rotation(DisplayRotation::Rotate90).unwrap();
+        display.set_contrast(0x20).unwrap();
+        display.flush().unwrap();
+        display.flush_all().unwrap();
+        for _ in 0..4 {
+            display.step_ticker(&mut ticker).unwrap();
+        }
+        display.set_pixel(20, 20, 1);
+        display.flush().unwrap();
+        display.stop_ticker();
+        display.flush().unwrap();
+        let (mut properties, _) = display.release_buffer();
+        let expected = properties.iface_mut();
+
+        let mut display: AsyncGraphicsMode<_> = builder.connect_async(RecordingInterface::new());
+        block_on(async {
+            display.set_command_lock(true).await.unwrap();
+            display.init().await.unwrap();
+            display.flush().await.unwrap();
+            display.set_pixel(3, 20, 1);
+            display.set_pixel(90, 40, 1);
+            display.flush().await.unwrap();
+            display
+                .set_rotation(DisplayRotation::Rotate90)
+                .await
+                .unwrap();
+            display.set_contrast(0x20).await.unwrap();
+            display.flush().await.unwrap();
+            display.flush_all().await.unwrap();
+            ticker.reset();
+            for _ in 0..4 {
+                display.step_ticker(&mut ticker).await.unwrap();
+            }
+            display.set_pixel(20, 20, 1);
+            display.flush().await.unwrap();
+            display.stop_ticker();
+            display.flush().await.unwrap();
+        });
+        let (recorded, _) = display.release();
+
+        assert!(expected.len > 2048);
+        assert_eq!(recorded.recorded(), expected.recorded());
+    }
+
+    #[test]
+    fn clean_flush_keeps_scrolling() {
+        let mut display: AsyncGraphicsMode<_> =
+            Builder::new().connect_async(RecordingInterface::new());
+        let pages = (Page::Page0, Page::Page7);
+
+        let len = block_on(async {
+            display.init().await.unwrap();
+            display.flush().await.unwrap();
+            display
+                .set_horizontal_scroll(ScrollDirection::Left, pages, (0, 127), NFrames::F2)
+                .await
+                .unwrap();
+            display.start_scroll().await.unwrap();
+            let len = display.iface.len;
+
+            display.flush().await.unwrap();
+            len
+        });
+
+        assert_eq!(display.iface.len, len);
+        assert!(display.mode.properties().is_scrolling());
+    }
+
+    #[test]
+    fn keeps_state_until_sent() {
+        let mut interface = RecordingInterface::new();
+        interface.fail_after(0);
+
+        let mut display: AsyncGraphicsMode<_> = Builder::new().connect_async(interface);
+        let contrast = display.mode.properties().get_contrast();
+        block_on(async {
+            display.set_pixel(3, 20, 1);
+            assert!(display.set_contrast(0x20).await.is_err());
+            assert!(display
+                .set_rotation(DisplayRotation::Rotate90)
+                .await
+                .is_err());
+            assert!(display.flush().await.is_err());
+        });
+
+        let properties = display.mode.properties();
+        assert_eq!(properties.get_contrast(), contrast);
+        assert!(matches!(
+            properties.get_rotation(),
+            DisplayRotation::Rotate0
+        ));
+        assert_eq!(display.mode.dirty_bytes(), 1024);
+
+        // Dropping the future before the commands have been sent
+        let mut display: AsyncGraphicsMode<_> = Builder::new().connect_async(PendingInterface);
+        let mut context = Context::from_waker(Waker::noop());
+
+        {
+            let mut future = pin!(display.set_command_lock(true));
+            assert!(future.as_mut().poll(&mut context).is_pending());
+        }
+
+        assert!(!display.mode.properties().is_command_locked());
+    }
+}
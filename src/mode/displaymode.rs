@@ -2,36 +2,51 @@
 
 use display_interface::WriteOnlyDataCommand;
 
-use crate::properties::DisplayProperties;
+use crate::{
+    displaysize::{DisplaySize128x64, PanelSize},
+    properties::DisplayProperties,
+};
 
 /// Display mode abstraction
 pub struct DisplayMode<MODE>(pub MODE);
 
 /// Trait with core functionality for display mode switching
-pub trait DisplayModeTrait<DI> {
+pub trait DisplayModeTrait<DI, SIZE = DisplaySize128x64> {
     /// Allocate all required data and initialise display for mode
-    fn new(properties: DisplayProperties<DI>) -> Self;
+    fn new(properties: DisplayProperties<DI, SIZE>) -> Self;
 
     /// Release resources for reuse with different mode
-    fn release(self) -> DisplayProperties<DI>;
+    fn release(self) -> DisplayProperties<DI, SIZE>;
 }
 
 impl<MODE> DisplayMode<MODE> {
     /// Setup display to run in requested mode
-    pub fn new<DI>(properties: DisplayProperties<DI>) -> Self
+    pub fn new<DI, SIZE>(properties: DisplayProperties<DI, SIZE>) -> Self
     where
         DI: WriteOnlyDataCommand,
-        MODE: DisplayModeTrait<DI>,
+        SIZE: PanelSize,
+        MODE: DisplayModeTrait<DI, SIZE>,
     {
         DisplayMode(MODE::new(properties))
     }
 
+    /// Release the display properties, e.g. to create a mode which needs more than the properties
+    pub fn release<DI, SIZE>(self) -> DisplayProperties<DI, SIZE>
+    where
+        DI: WriteOnlyDataCommand,
+        SIZE: PanelSize,
+        MODE: DisplayModeTrait<DI, SIZE>,
+    {
+        self.0.release()
+    }
+
     /// Change into any mode implementing DisplayModeTrait
     // TODO: Figure out how to stay as generic DisplayMode but act as particular mode
-    pub fn into<DI, NMODE: DisplayModeTrait<DI>>(self) -> NMODE
+    pub fn into<DI, SIZE, NMODE: DisplayModeTrait<DI, SIZE>>(self) -> NMODE
     where
         DI: WriteOnlyDataCommand,
-        MODE: DisplayModeTrait<DI>,
+        SIZE: PanelSize,
+        MODE: DisplayModeTrait<DI, SIZE>,
     {
         let properties = self.0.release();
         NMODE::new(properties)
@@ -0,0 +1,340 @@
+//! Flush of a [GraphicsMode] display buffer which is sent in steps
+
+use display_interface::WriteOnlyDataCommand;
+
+use crate::{
+    displaysize::PanelSize,
+    error::{Error, Operation},
+    mode::graphics::GraphicsMode,
+};
+
+/// Progress of a [FlushJob]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlushProgress {
+    /// Number of data bytes the job has sent so far
+    pub sent: usize,
+    /// Number of data bytes left to send for the parts of the buffer that changed
+    pub remaining: usize,
+}
+
+impl FlushProgress {
+    /// Whether all changes of the buffer have been sent
+    pub fn is_done(&self) -> bool {
+        self.remaining == 0
+    }
+}
+
+/// Window of the display buffer which is being sent
+#[derive(Debug, Clone, Copy)]
+struct Window {
+    columns: (u8, u8),
+    /// Pages of the window which haven't been sent completely yet
+    pages: (usize, usize),
+    /// Generation of the display buffer when the window was started
+    generation: u32,
+    /// Number of data bytes of the first page of the window sent so far
+    sent: usize,
+}
+
+/// Flush which sends the changed parts of the display buffer in steps, created by
+/// [GraphicsMode::begin_flush]
+///
+/// Each call to `poll` sends the next page of the window `flush` would send, or the number of
+/// bytes set using `with_chunk_size`. Each page is recorded as sent as soon as all of its bytes
+/// have been sent. The buffer can be drawn to between two calls. If it changes, or the display is
+/// flushed by other means, the next call continues with the pages which haven't been sent yet or
+/// changed again, so that the display ends up showing the current content of the buffer. Use
+/// `cancel` to stop the job early.
+#[derive(Debug, Clone, Copy)]
+pub struct FlushJob {
+    chunk_size: Option<usize>,
+    window: Option<Window>,
+    sent: usize,
+    restart_scroll: bool,
+}
+
+impl FlushJob {
+    pub(crate) fn new() -> Self {
+        FlushJob {
+            chunk_size: None,
+            window: None,
+            sent: 0,
+            restart_scroll: false,
+        }
+    }
+
+    /// Send at most `bytes` data bytes per call to `poll` instead of one page. The bytes sent by
+    /// a single call never span more than one window.
+    pub fn with_chunk_size(self, bytes: usize) -> Self {
+        FlushJob {
+            chunk_size: Some(bytes.max(1)),
+            ..self
+        }
+    }
+
+    /// Send the next step of the flush to `display` and report the progress. Once all changes
+    /// have been sent, an active scroll stopped by the job is restarted and further calls do
+    /// nothing until the buffer changes again.
+    ///
+    /// If sending fails, the page in progress is started again by the next call.
+    pub fn poll<DI, SIZE, BUF>(
+        &mut self,
+        display: &mut GraphicsMode<DI, SIZE, BUF>,
+    ) -> Result<FlushProgress, Error>
+    where
+        DI: WriteOnlyDataCommand,
+        SIZE: PanelSize,
+        BUF: AsMut<[u8]> + AsRef<[u8]>,
+    {
+        display.check_geometry(Operation::Flush)?;
+
+        let result = self.step(display);
+        if result.is_err() {
+            self.window = None;
+        }
+        result?;
+
+        let in_progress = self.window.map_or(0, |window| window.sent);
+        let remaining = display.dirty_bytes().saturating_sub(in_progress);
+
+        if remaining == 0 && self.restart_scroll {
+            self.restart_scroll = false;
+            display.start_scroll()?;
+        }
+
+        Ok(FlushProgress {
+            sent: self.sent,
+            remaining,
+        })
+    }
+
+    /// Stop the flush. The parts of the buffer which haven't been sent yet are sent by the next
+    /// flush, and an active scroll stopped by the job is restarted.
+    pub fn cancel<DI, SIZE, BUF>(
+        self,
+        display: &mut GraphicsMode<DI, SIZE, BUF>,
+    ) -> Result<(), Error>
+    where
+        DI: WriteOnlyDataCommand,
+        SIZE: PanelSize,
+        BUF: AsMut<[u8]> + AsRef<[u8]>,
+    {
+        if self.restart_scroll {
+            display.start_scroll()?;
+        }
+
+        Ok(())
+    }
+
+    /// Start the next window if needed and send its next chunk
+    fn step<DI, SIZE, BUF>(
+        &mut self,
+        display: &mut GraphicsMode<DI, SIZE, BUF>,
+    ) -> Result<(), Error>
+    where
+        DI: WriteOnlyDataCommand,
+        SIZE: PanelSize,
+        BUF: AsMut<[u8]> + AsRef<[u8]>,
+    {
+        if self
+            .window
+            .is_some_and(|window| window.generation != display.generation())
+        {
+            self.window = None;
+        }
+
+        if self.window.is_none() {
+            // Without changes, an active scroll keeps running
+            if display.next_dirty_window().is_none() {
+                return Ok(());
+            }
+
+            display.resend_if_scrolled();
+        }
+
+        let mut window = match self.window {
+            Some(window) => window,
+            None => match display.next_dirty_window() {
+                Some((columns, pages)) => {
+                    // Setting the draw area stops an active scroll
+                    self.restart_scroll |= display.properties().is_scrolling();
+
+                    let (start, end) = display.window_area(columns, pages);
+                    display.properties_mut().set_draw_area(start, end)?;
+
+                    Window {
+                        columns,
+                        pages,
+                        generation: display.generation(),
+                        sent: 0,
+                    }
+                }
+                None => return Ok(()),
+            },
+        };
+
+        let (start_column, end_column) = window.columns;
+        let page_bytes = (end_column - start_column) as usize + 1;
+        let chunk_size = self.chunk_size.unwrap_or(page_bytes);
+
+        let mut skip = window.sent;
+        for range in display.window_ranges(window.columns, window.pages) {
+            if skip >= range.len() {
+                skip -= range.len();
+                continue;
+            }
+
+            let start = range.start + skip;
+            let end = range.end.min(start + chunk_size);
+            display.draw_range(start..end)?;
+
+            window.sent += end - start;
+            self.sent += end - start;
+            break;
+        }
+
+        // Record completed pages right away, so that a change of the buffer only causes the
+        // pages which changed again to be resent
+        let (start_page, end_page) = window.pages;
+        let sent_pages = (window.sent / page_bytes).min(end_page - start_page);
+        display.clear_dirty(window.columns, (start_page, start_page + sent_pages));
+        window.pages.0 += sent_pages;
+        window.sent -= sent_pages * page_bytes;
+
+        self.window = if window.pages.0 < end_page {
+            Some(window)
+        } else {
+            None
+        };
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        builder::Builder,
+        command::{NFrames, Page, ScrollDirection},
+        mode::GraphicsMode,
+        test_helpers::{initialised_display, RecordingInterface},
+    };
+
+    #[test]
+    fn sends_same_bytes_as_flush() {
+        let builder = Builder::new();
+        let mut expected: GraphicsMode<_> = builder.connect(RecordingInterface::new()).into();
+        let mut display: GraphicsMode<_> = builder.connect(RecordingInterface::new()).into();
+
+        expected.init().unwrap();
+        expected.flush().unwrap();
+        display.init().unwrap();
+
+        let mut job = display.begin_flush();
+        for _ in 0..7 {
+            assert!(!job.poll(&mut display).unwrap().is_done());
+        }
+        assert!(job.poll(&mut display).unwrap().is_done());
+
+        for (i, chunk_size) in [1, 100, 2000].iter().enumerate() {
+            for display in [&mut expected, &mut display] {
+                display.set_pixel(3, 20, (i % 2 == 0).into());
+                display.set_pixel(90, 40, 1);
+                display.set_pixel(i as u32, 63, 1);
+            }
+            expected.flush().unwrap();
+
+            let mut job = display.begin_flush().with_chunk_size(*chunk_size);
+            while !job.poll(&mut display).unwrap().is_done() {}
+        }
+
+        let (mut expected, _) = expected.release_buffer();
+        let (mut display, _) = display.release_buffer();
+        assert_eq!(
+            display.iface_mut().recorded(),
+            expected.iface_mut().recorded()
+        );
+    }
+
+    #[test]
+    fn restarts_window_when_buffer_changes() {
+        let mut display = initialised_display();
+
+        let mut job = display.begin_flush();
+        job.poll(&mut display).unwrap();
+        display.set_pixel(0, 0, 1);
+
+        let progress = job.poll(&mut display).unwrap();
+        assert_eq!(progress.sent, 128 + 1);
+        assert_eq!(progress.remaining, 1024 - 128);
+
+        while !job.poll(&mut display).unwrap().is_done() {}
+
+        let mut job = display.begin_flush();
+        assert_eq!(job.poll(&mut display).unwrap().sent, 0);
+    }
+
+    #[test]
+    fn finishes_while_buffer_keeps_changing() {
+        let mut display = initialised_display();
+
+        let mut job = display.begin_flush();
+        let done = (1..100).find(|&poll| {
+            if poll % 7 == 0 {
+                display.set_pixel(poll, 0, 1);
+            }
+            job.poll(&mut display).unwrap().is_done()
+        });
+        assert_eq!(done, Some(9));
+    }
+
+    #[test]
+    fn follows_zoom_changes() {
+        let mut display = initialised_display();
+
+        let mut job = display.begin_flush();
+        for _ in 0..5 {
+            job.poll(&mut display).unwrap();
+        }
+
+        display.set_zoom(true).unwrap();
+        let progress = job.poll(&mut display).unwrap();
+        assert_eq!(progress.sent, 5 * 128);
+        assert!(progress.is_done());
+
+        display.set_zoom(false).unwrap();
+        let progress = job.poll(&mut display).unwrap();
+        assert_eq!(progress.sent, 6 * 128);
+        assert_eq!(progress.remaining, 2 * 128);
+    }
+
+    #[test]
+    fn finished_job_keeps_scrolling() {
+        let mut display = initialised_display();
+        display
+            .set_horizontal_scroll(
+                ScrollDirection::Left,
+                (Page::Page0, Page::Page7),
+                (0, 127),
+                NFrames::F2,
+            )
+            .unwrap();
+        display.start_scroll().unwrap();
+
+        let mut job = display.begin_flush();
+        while !job.poll(&mut display).unwrap().is_done() {}
+        assert!(display.properties().is_scrolling());
+
+        let len = display.properties_mut().iface_mut().len;
+        let progress = job.poll(&mut display).unwrap();
+        assert_eq!(progress.sent, 1024);
+        assert!(progress.is_done());
+        assert_eq!(display.properties_mut().iface_mut().len, len);
+        assert!(display.properties().is_scrolling());
+
+        // The first change resends the whole buffer, as the scroll moved the display RAM
+        display.set_pixel(3, 20, 1);
+        let progress = job.poll(&mut display).unwrap();
+        assert_eq!(progress.remaining, 1024 - 128);
+    }
+}
@@ -15,51 +15,162 @@
 //! display.flush().unwrap();
 //! ```
 
-use display_interface::{DisplayError, WriteOnlyDataCommand};
+use core::ops::Range;
+
+use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
 use hal::{blocking::delay::DelayMs, digital::v2::OutputPin};
 
 use crate::{
+    command::{AddrMode, FadeMode, NFrames, Page, ScrollDirection},
     displayrotation::DisplayRotation,
-    mode::displaymode::DisplayModeTrait,
+    displaysize::{DisplaySize128x64, PanelSize},
+    error::{Error, Operation},
+    mode::{displaymode::DisplayModeTrait, flushjob::FlushJob},
     properties::DisplayProperties,
+    ticker::Ticker,
 };
 
-const BUFFER_SIZE: usize = 128 * 64 / 8;
+const PAGES: usize = 8;
 
 /// Graphics mode handler
-pub struct GraphicsMode<DI>
+///
+/// The display buffer is stored in `BUF`, which defaults to an inline array sized to the display
+/// by the [PanelSize] type `SIZE`:
+///
+/// ```rust
+///# #[path = "../test_helpers.rs"]
+///# mod test_helpers;
+///# use test_helpers::StubInterface;
+///# let interface = StubInterface;
+/// use ssd1309::prelude::*;
+///
+/// let mut display: GraphicsMode<_, DisplaySize64x48> = ssd1309::Builder::new()
+///     .with_size(DisplaySize64x48)
+///     .connect(interface)
+///     .into();
+///
+/// display.init().unwrap();
+/// ```
+///
+/// An inline array which doesn't match the size of the display fails to compile. The geometry of a
+/// [CustomSize] is only known at runtime, so any array of 1 to 1024 bytes is accepted for it. Any
+/// other storage, e.g. a `&'static mut [u8]` buffer placed in a static, can be used with
+/// [GraphicsMode::with_buffer]. In these two cases, a buffer which is too small for the display is
+/// rejected by `init`, which fails with `Error::InvalidParameter`, as do all flush methods.
+///
+/// [CustomSize]: crate::prelude::CustomSize
+pub struct GraphicsMode<DI, SIZE = DisplaySize128x64, BUF = <SIZE as PanelSize>::Buffer>
 where
     DI: WriteOnlyDataCommand,
+    SIZE: PanelSize,
+    BUF: AsMut<[u8]> + AsRef<[u8]>,
 {
-    properties: DisplayProperties<DI>,
-    buffer: [u8; BUFFER_SIZE],
+    properties: DisplayProperties<DI, SIZE>,
+    buffer: BUF,
+    /// Inclusive range of changed columns of each page since the last flush
+    dirty: [Option<(u8, u8)>; PAGES],
+    /// Pages from `.0.0` up to, but not including, `.0.1` and inclusive range of columns owned by
+    /// the [Ticker] last stepped, which are left out by `flush`
+    ticker_area: Option<((usize, usize), (u8, u8))>,
+    /// Changed whenever the buffer, the draw position or the geometry of the display changes
+    /// outside of a [FlushJob], which restarts the window the job is sending
+    generation: u32,
+    /// Whether a scroll moved the content of the display RAM since the buffer was last sent
+    scrolled: bool,
+}
+
+/// State of a [GraphicsMode] besides the display buffer, saved to undo changes which didn't reach
+/// the display
+#[cfg(feature = "async")]
+pub(crate) struct SavedState<DI, SIZE> {
+    properties: DisplayProperties<DI, SIZE>,
+    dirty: [Option<(u8, u8)>; PAGES],
+    generation: u32,
+    scrolled: bool,
+    ticker_area: Option<((usize, usize), (u8, u8))>,
 }
 
-impl<DI> DisplayModeTrait<DI> for GraphicsMode<DI>
+impl<DI, SIZE, const N: usize> DisplayModeTrait<DI, SIZE> for GraphicsMode<DI, SIZE, [u8; N]>
 where
     DI: WriteOnlyDataCommand,
+    SIZE: PanelSize,
 {
     /// Create new GraphicsMode instance
-    fn new(properties: DisplayProperties<DI>) -> Self {
-        GraphicsMode {
-            properties,
-            buffer: [0; BUFFER_SIZE],
-        }
+    fn new(properties: DisplayProperties<DI, SIZE>) -> Self {
+        #[allow(clippy::let_unit_value)]
+        let _ = Self::VALID_BUFFER_SIZE;
+
+        GraphicsMode::with_buffer(properties, [0; N])
     }
 
     /// Release all resources used by GraphicsMode
-    fn release(self) -> DisplayProperties<DI> {
+    fn release(self) -> DisplayProperties<DI, SIZE> {
         self.properties
     }
 }
 
-impl<DI> GraphicsMode<DI>
+impl<DI, SIZE, const N: usize> GraphicsMode<DI, SIZE, [u8; N]>
+where
+    DI: WriteOnlyDataCommand,
+    SIZE: PanelSize,
+{
+    /// Evaluated when a GraphicsMode is created to reject buffers which don't match the display
+    /// size at compile time
+    const VALID_BUFFER_SIZE: () = assert!(
+        N >= SIZE::MIN_BUFFER_SIZE && N <= SIZE::BUFFER_SIZE,
+        "GraphicsMode buffer size must match the display size"
+    );
+}
+
+impl<DI, SIZE, BUF> GraphicsMode<DI, SIZE, BUF>
 where
     DI: WriteOnlyDataCommand,
+    SIZE: PanelSize,
+    BUF: AsMut<[u8]> + AsRef<[u8]>,
 {
+    /// Create a new GraphicsMode instance which stores the display buffer in `buffer`, e.g. a
+    /// `&'static mut [u8]` to keep the buffer off the stack. The current content of `buffer` is
+    /// kept and sent to the display by the first flush.
+    ///
+    /// ```rust
+    ///# #[path = "../test_helpers.rs"]
+    ///# mod test_helpers;
+    ///# use test_helpers::StubInterface;
+    ///# let interface = StubInterface;
+    /// use ssd1309::prelude::*;
+    ///
+    /// static mut BUFFER: [u8; 1024] = [0; 1024];
+    ///
+    /// let buffer: &'static mut [u8] = unsafe { &mut *core::ptr::addr_of_mut!(BUFFER) };
+    /// let properties = ssd1309::Builder::new().connect(interface).release();
+    /// let mut display = GraphicsMode::with_buffer(properties, buffer);
+    ///
+    /// display.init().unwrap();
+    /// ```
+    pub fn with_buffer(properties: DisplayProperties<DI, SIZE>, buffer: BUF) -> Self {
+        // The content of the display RAM is unknown, so the first flush sends the whole buffer
+        let mut mode = GraphicsMode {
+            properties,
+            buffer,
+            dirty: [None; PAGES],
+            ticker_area: None,
+            generation: 0,
+            scrolled: false,
+        };
+        mode.mark_all_dirty();
+
+        mode
+    }
+
+    /// Release the display properties and the display buffer
+    pub fn release_buffer(self) -> (DisplayProperties<DI, SIZE>, BUF) {
+        (self.properties, self.buffer)
+    }
+
     /// Clear the display buffer. You need to call `disp.flush()` for any effect on the screen
     pub fn clear(&mut self) {
-        self.buffer = [0; BUFFER_SIZE];
+        self.buffer.as_mut().fill(0);
+        self.mark_all_dirty();
     }
 
     /// Reset display. This is very important on the SSD1309!
@@ -69,92 +180,449 @@ where
         &mut self,
         rst: &mut RST,
         delay: &mut DELAY,
-    ) -> Result<(), PinE>
+    ) -> Result<(), Error<PinE>>
     where
         RST: OutputPin<Error = PinE>,
         DELAY: DelayMs<u8>,
     {
-        rst.set_high()?;
+        rst.set_high().map_err(Error::Pin)?;
         delay.delay_ms(10);
-        rst.set_low()?;
+        rst.set_low().map_err(Error::Pin)?;
         delay.delay_ms(10);
-        rst.set_high()?;
+        rst.set_high().map_err(Error::Pin)?;
         delay.delay_ms(10);
         Ok(())
     }
 
-    /// Write out data to display
-    pub fn flush(&mut self) -> Result<(), DisplayError> {
-        let display_size = self.properties.get_size();
+    /// Write out the parts of the buffer that changed since the last flush to the display. Each
+    /// changed page is sent as one window of its changed columns, and consecutive pages with the
+    /// same changed columns are combined into a single window. While zoom is enabled, only the
+    /// visible upper half of the buffer is sent. If nothing changed, nothing is sent and an
+    /// active scroll keeps running. Otherwise an active scroll is stopped while the display RAM is
+    /// rewritten and restarted afterwards. As the scroll moved the content of the display RAM,
+    /// the whole buffer is sent in that case.
+    pub fn flush(&mut self) -> Result<(), Error> {
+        self.check_geometry(Operation::Flush)?;
 
-        // Ensure the display buffer is at the origin of the display before we send the full frame
-        // to prevent accidental offsets
-        let (display_width, display_height) = display_size.dimensions();
-        let column_offset = display_size.column_offset();
-        self.properties.set_draw_area(
-            (column_offset, 0),
-            (display_width + column_offset, display_height),
-        )?;
+        if self.next_dirty_window().is_none() {
+            return Ok(());
+        }
+
+        self.resend_if_scrolled();
+
+        let scrolling = self.properties.is_scrolling();
+        let mut flushed = false;
+
+        while let Some((columns, pages)) = self.next_dirty_window() {
+            self.flush_window(columns, pages)?;
+            self.clear_dirty(columns, pages);
+            flushed = true;
+        }
 
-        let length = (display_width as usize) * (display_height as usize) / 8;
+        if flushed && scrolling {
+            self.properties.start_scroll()?;
+        }
 
-        self.properties.draw(&self.buffer[..length])
+        Ok(())
     }
 
-    /// Turn a pixel on or off. A non-zero `value` is treated as on, `0` as off. If the X and Y
-    /// coordinates are out of the bounds of the display, this method call is a noop.
-    pub fn set_pixel(&mut self, x: u32, y: u32, value: u8) {
-        let (display_width, _) = self.properties.get_size().dimensions();
-        let display_rotation = self.properties.get_rotation();
+    /// Get the next window of changed columns and pages to be sent by `flush`, combining
+    /// consecutive pages with the same changed columns
+    pub(crate) fn next_dirty_window(&self) -> Option<((u8, u8), (usize, usize))> {
+        let pages = self.visible_pages();
+        let page = (0..pages).find(|&page| self.dirty_columns(page).is_some())?;
+        let columns = self.dirty_columns(page)?;
+
+        let mut end_page = page + 1;
+        while end_page < pages && self.dirty_columns(end_page) == Some(columns) {
+            end_page += 1;
+        }
+
+        Some((columns, (page, end_page)))
+    }
+
+    /// Get the changed columns of a page which `flush` sends next. The columns of a ticker are
+    /// left out. If the page changed on both sides of the ticker, the columns to its left come
+    /// first.
+    fn dirty_columns(&self, page: usize) -> Option<(u8, u8)> {
+        let (start, end) = self.dirty[page]?;
 
-        let idx = match display_rotation {
-            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => {
-                if x >= display_width as u32 {
-                    return;
+        match self.ticker_columns(page) {
+            Some((ticker_start, ticker_end)) if start <= ticker_end && end >= ticker_start => {
+                if start < ticker_start {
+                    Some((start, ticker_start - 1))
+                } else if end > ticker_end {
+                    Some((ticker_end + 1, end))
+                } else {
+                    None
                 }
-                ((y as usize) / 8 * display_width as usize) + (x as usize)
             }
+            _ => Some((start, end)),
+        }
+    }
+
+    /// Get the columns of a page owned by a ticker
+    fn ticker_columns(&self, page: usize) -> Option<(u8, u8)> {
+        let ((start_page, end_page), columns) = self.ticker_area?;
+
+        (start_page..end_page).contains(&page).then_some(columns)
+    }
+
+    /// Get the number of bytes `flush` sends for the parts of the buffer that changed
+    pub(crate) fn dirty_bytes(&self) -> usize {
+        (0..self.visible_pages())
+            .filter_map(|page| Some((page, self.dirty[page]?)))
+            .map(|(page, (start, end))| {
+                let ticker = self
+                    .ticker_columns(page)
+                    .map_or(0, |(ticker_start, ticker_end)| {
+                        (ticker_end.min(end) as usize + 1)
+                            .saturating_sub(ticker_start.max(start) as usize)
+                    });
+
+                (end - start) as usize + 1 - ticker
+            })
+            .sum()
+    }
 
-            DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => {
-                if y >= display_width as u32 {
-                    return;
+    /// Get the number of pages of the buffer which are visible on the display
+    fn visible_pages(&self) -> usize {
+        let (_, display_height) = self.properties.get_visible_size();
+
+        (display_height as usize).div_ceil(8).min(PAGES)
+    }
+
+    /// Record that an inclusive range of columns of the pages from `pages.0` up to, but not
+    /// including, `pages.1` has been sent. Changed columns on one side of the range are kept.
+    pub(crate) fn clear_dirty(&mut self, columns: (u8, u8), pages: (usize, usize)) {
+        for dirty in &mut self.dirty[pages.0..pages.1.min(PAGES)] {
+            *dirty = match *dirty {
+                Some((start, end)) if columns.0 <= start && columns.1 >= end => None,
+                Some((start, end)) if columns.0 <= start && columns.1 >= start => {
+                    Some((columns.1 + 1, end))
                 }
-                ((x as usize) / 8 * display_width as usize) + (y as usize)
-            }
+                Some((start, end)) if columns.0 <= end && columns.1 >= end => {
+                    Some((start, columns.0 - 1))
+                }
+                dirty => dirty,
+            };
+        }
+    }
+
+    /// Send an inclusive range of columns of the pages from `pages.0` up to, but not including,
+    /// `pages.1` to the display as a single window
+    fn flush_window(&mut self, columns: (u8, u8), pages: (usize, usize)) -> Result<(), Error> {
+        self.invalidate_flush_jobs();
+
+        let (start, end) = self.window_area(columns, pages);
+        self.properties.set_draw_area(start, end)?;
+
+        for range in self.window_ranges(columns, pages) {
+            self.draw_range(range)?;
+        }
+
+        Ok(())
+    }
+
+    /// Get the draw area of a window of columns and pages
+    pub(crate) fn window_area(
+        &self,
+        columns: (u8, u8),
+        pages: (usize, usize),
+    ) -> ((u8, u8), (u8, u8)) {
+        let column_offset = self.properties.column_offset();
+        let (start_column, end_column) = columns;
+        let (start_page, end_page) = pages;
+
+        (
+            (start_column + column_offset, (start_page * 8) as u8),
+            (end_column + 1 + column_offset, (end_page * 8) as u8),
+        )
+    }
+
+    /// Get the ranges of the buffer which fill a window, in the order they are drawn. A window
+    /// spanning the whole width of the display is a single range, otherwise each page is drawn
+    /// separately.
+    pub(crate) fn window_ranges(
+        &self,
+        columns: (u8, u8),
+        pages: (usize, usize),
+    ) -> impl Iterator<Item = Range<usize>> {
+        let (display_width, _) = self.properties.get_visible_size();
+        let width = display_width as usize;
+        let (start_column, end_column) = (columns.0 as usize, columns.1 as usize);
+        let (start_page, end_page) = pages;
+
+        let step = if start_column == 0 && end_column + 1 == width {
+            end_page - start_page
+        } else {
+            1
         };
 
-        if idx >= self.buffer.len() {
-            return;
+        (start_page..end_page)
+            .step_by(step.max(1))
+            .map(move |page| {
+                let last_page = (page + step).min(end_page) - 1;
+
+                page * width + start_column..last_page * width + end_column + 1
+            })
+    }
+
+    /// Draw a range of the buffer at the current position of the draw area
+    pub(crate) fn draw_range(&mut self, range: Range<usize>) -> Result<(), Error> {
+        self.properties.draw(&self.buffer.as_ref()[range])
+    }
+
+    /// Write out the whole buffer to the display, regardless of which parts changed. The frame is
+    /// sent as a single contiguous data write using horizontal addressing mode, which is enabled
+    /// by `init`. While zoom is enabled, only the visible upper half of the buffer is sent. An
+    /// active scroll is stopped while the display RAM is rewritten and restarted afterwards.
+    pub fn flush_all(&mut self) -> Result<(), Error> {
+        self.check_geometry(Operation::Flush)?;
+
+        let scrolling = self.properties.is_scrolling();
+        let (start, end, length) = self.frame_area();
+
+        self.invalidate_flush_jobs();
+
+        // Ensure the display buffer is at the origin of the display before we send the full frame
+        // to prevent accidental offsets
+        self.properties.set_draw_area(start, end)?;
+        self.draw_range(0..length)?;
+        self.clear_all_dirty(end.1 as usize / 8);
+
+        if scrolling {
+            self.properties.start_scroll()?;
         }
 
-        let (byte, bit) = match display_rotation {
-            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => {
-                let byte =
-                    &mut self.buffer[((y as usize) / 8 * display_width as usize) + (x as usize)];
-                let bit = 1 << (y % 8);
+        Ok(())
+    }
 
-                (byte, bit)
-            }
-            DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => {
-                let byte =
-                    &mut self.buffer[((x as usize) / 8 * display_width as usize) + (y as usize)];
-                let bit = 1 << (x % 8);
+    /// Get the exact bytes `flush_all` sends to the display, e.g. to transfer them using DMA. The
+    /// first value holds the command bytes which set up the display RAM window, the second value
+    /// the frame data, which can be sent as a single data write afterwards.
+    ///
+    /// The bytes rely on horizontal addressing mode as set up by `init`. Scrolling must be stopped
+    /// and the command lock disabled while they are sent.
+    ///
+    /// Fails with `Error::InvalidParameter` if the display buffer is too small for the display
+    /// size.
+    pub fn flush_bytes(&self) -> Result<([u8; 6], &[u8]), Error> {
+        self.check_geometry(Operation::Flush)?;
 
-                (byte, bit)
-            }
+        let (start, end, length) = self.frame_area();
+
+        Ok((
+            DisplayProperties::<DI, SIZE>::window_commands(start, end),
+            &self.buffer.as_ref()[..length],
+        ))
+    }
+
+    /// Get the draw area and buffer length of the visible part of the display
+    pub(crate) fn frame_area(&self) -> ((u8, u8), (u8, u8), usize) {
+        let (display_width, display_height) = self.properties.get_visible_size();
+        let column_offset = self.properties.column_offset();
+        let pages = display_height.div_ceil(8);
+        let length = (display_width as usize) * (pages as usize);
+
+        (
+            (column_offset, 0),
+            (display_width + column_offset, pages * 8),
+            length,
+        )
+    }
+
+    /// Turn a pixel on or off. A non-zero `value` is treated as on, `0` as off. If the X and Y
+    /// coordinates are out of the bounds of the display, this method call is a noop.
+    pub fn set_pixel(&mut self, x: u32, y: u32, value: u8) {
+        let (display_width, display_height) = self.properties.get_size().dimensions();
+        let (column, row) = self.to_physical(x, y);
+
+        if column >= display_width as u32 || row >= display_height as u32 {
+            return;
+        }
+
+        let idx = ((row as usize) / 8 * display_width as usize) + (column as usize);
+
+        let byte = match self.buffer.as_mut().get_mut(idx) {
+            Some(byte) => byte,
+            None => return,
         };
+        let previous = *byte;
+        let bit = 1 << (row % 8);
 
         if value == 0 {
             *byte &= !bit;
         } else {
             *byte |= bit;
         }
+
+        if *byte != previous {
+            self.mark_dirty(row as usize / 8, (column as u8, column as u8));
+        }
+    }
+
+    /// Map a pixel position to a column and row of the display buffer, taking into account the
+    /// display rotation
+    fn to_physical(&self, x: u32, y: u32) -> (u32, u32) {
+        match self.properties.get_rotation() {
+            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => (x, y),
+            DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => (y, x),
+        }
+    }
+
+    /// Check that the display size is valid and the display buffer can hold the whole display
+    pub(crate) fn check_geometry(&self, operation: Operation) -> Result<(), Error> {
+        let display_size = self.properties.get_size();
+
+        if !display_size.is_valid() || self.buffer.as_ref().len() < display_size.buffer_size() {
+            return Err(Error::InvalidParameter(operation));
+        }
+
+        Ok(())
+    }
+
+    /// Start a flush which is sent in steps by polling the returned [FlushJob], e.g. to interleave
+    /// it with other work in a superloop:
+    ///
+    /// ```rust
+    ///# #[path = "../test_helpers.rs"]
+    ///# mod test_helpers;
+    ///# use test_helpers::StubInterface;
+    ///# let interface = StubInterface;
+    /// use ssd1309::prelude::*;
+    ///
+    /// let mut display: GraphicsMode<_> = ssd1309::Builder::new().connect(interface).into();
+    /// display.init().unwrap();
+    ///
+    /// let mut job = display.begin_flush();
+    /// while !job.poll(&mut display).unwrap().is_done() {
+    ///     // Time-critical work, which may also draw to the display buffer
+    /// }
+    /// ```
+    pub fn begin_flush(&self) -> FlushJob {
+        FlushJob::new()
+    }
+
+    /// Get the generation of the buffer and draw position, see [FlushJob]
+    pub(crate) fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    /// Restart the window of any [FlushJob] in progress, as the buffer, the draw position or the
+    /// geometry of the display changed
+    fn invalidate_flush_jobs(&mut self) {
+        self.generation = self.generation.wrapping_add(1);
+    }
+
+    /// Record that an inclusive range of columns of a page has changed since the last flush
+    fn mark_dirty(&mut self, page: usize, columns: (u8, u8)) {
+        self.invalidate_flush_jobs();
+
+        if let Some(dirty) = self.dirty.get_mut(page) {
+            *dirty = match *dirty {
+                Some((start, end)) => Some((start.min(columns.0), end.max(columns.1))),
+                None => Some(columns),
+            };
+        }
+    }
+
+    /// Record that the whole buffer has changed since the last flush
+    fn mark_all_dirty(&mut self) {
+        self.invalidate_flush_jobs();
+
+        let (display_width, _) = self.properties.get_size().dimensions();
+
+        self.dirty = [Some((0, display_width.saturating_sub(1))); PAGES];
+    }
+
+    /// Record that the first `pages` pages of the whole buffer have been sent
+    pub(crate) fn clear_all_dirty(&mut self, pages: usize) {
+        self.clear_dirty((0, u8::MAX), (0, pages));
+        self.scrolled = false;
+    }
+
+    /// Record that an active scroll moved the content of the display RAM, so that the next flush
+    /// with changes sends the whole buffer
+    pub(crate) fn mark_scrolled(&mut self) {
+        if self.properties.is_scrolling() {
+            self.scrolled = true;
+        }
+    }
+
+    /// Record that the whole buffer has changed if a scroll moved the content of the display RAM
+    /// since the buffer was last sent. Called before the changes of the buffer are sent.
+    pub(crate) fn resend_if_scrolled(&mut self) {
+        self.mark_scrolled();
+
+        if self.scrolled {
+            self.scrolled = false;
+            self.mark_all_dirty();
+        }
     }
 
     /// Display is set up in column mode, i.e. a byte walks down a column of 8 pixels from
     /// column 0 on the left, to column _n_ on the right
-    pub fn init(&mut self) -> Result<(), DisplayError> {
-        self.properties.init_column_mode()
+    ///
+    /// The display is switched to horizontal addressing mode afterwards, which the display buffer
+    /// is sent in. This replaces any addressing mode set by a custom
+    /// [InitSequence](crate::InitSequence).
+    pub fn init(&mut self) -> Result<(), Error> {
+        self.init_with(|iface, sequence| iface.send_commands(DataFormat::U8(sequence)))
+    }
+
+    /// Initialise the display, handing any custom init sequence to `send_sequence`. See
+    /// [DisplayProperties::init_column_mode].
+    pub(crate) fn init_with<F>(&mut self, send_sequence: F) -> Result<(), Error>
+    where
+        F: FnOnce(&mut DI, &'static [u8]) -> Result<(), DisplayError>,
+    {
+        self.check_geometry(Operation::Init)?;
+        self.properties.init_with(send_sequence)?;
+
+        // Allow full frames to be sent in a single burst
+        self.properties.set_addr_mode(AddrMode::Horizontal)
+    }
+
+    /// Get the display properties
+    pub(crate) fn properties(&self) -> &DisplayProperties<DI, SIZE> {
+        &self.properties
+    }
+
+    /// Get the display properties for sending commands
+    pub(crate) fn properties_mut(&mut self) -> &mut DisplayProperties<DI, SIZE> {
+        &mut self.properties
+    }
+
+    /// Get the display buffer
+    #[cfg(feature = "async")]
+    pub(crate) fn buffer(&self) -> &[u8] {
+        self.buffer.as_ref()
+    }
+
+    /// Save the state besides the display buffer
+    #[cfg(feature = "async")]
+    pub(crate) fn save_state(&self) -> SavedState<DI, SIZE>
+    where
+        DI: Clone,
+    {
+        SavedState {
+            properties: self.properties.clone(),
+            dirty: self.dirty,
+            generation: self.generation,
+            scrolled: self.scrolled,
+            ticker_area: self.ticker_area,
+        }
+    }
+
+    /// Restore a state saved by `save_state`
+    #[cfg(feature = "async")]
+    pub(crate) fn restore_state(&mut self, state: SavedState<DI, SIZE>) {
+        self.properties = state.properties;
+        self.dirty = state.dirty;
+        self.generation = state.generation;
+        self.scrolled = state.scrolled;
+        self.ticker_area = state.ticker_area;
     }
 
     /// Get display dimensions, taking into account the current rotation of the display
@@ -162,21 +630,146 @@ where
         self.properties.get_dimensions()
     }
 
-    /// Set the display rotation
-    pub fn set_rotation(&mut self, rot: DisplayRotation) -> Result<(), DisplayError> {
-        self.properties.set_rotation(rot)
+    /// Set the display rotation. The whole buffer is sent by the next flush.
+    pub fn set_rotation(&mut self, rot: DisplayRotation) -> Result<(), Error> {
+        self.properties.set_rotation(rot)?;
+        self.mark_all_dirty();
+
+        Ok(())
+    }
+
+    /// Mirror the display horizontally and/or vertically. See [DisplayProperties::set_mirror]
+    /// for details. The whole buffer is sent by the next flush.
+    pub fn set_mirror(&mut self, horizontal: bool, vertical: bool) -> Result<(), Error> {
+        self.properties.set_mirror(horizontal, vertical)?;
+        self.mark_all_dirty();
+
+        Ok(())
     }
 
     /// Turn the display on or off. The display can be drawn to and retains all
     /// of its memory even while off.
-    pub fn display_on(&mut self, on: bool) -> Result<(), DisplayError> {
+    pub fn display_on(&mut self, on: bool) -> Result<(), Error> {
         self.properties.display_on(on)
     }
 
     /// Set the display contrast
-    pub fn set_contrast(&mut self, contrast: u8) -> Result<(), DisplayError> {
+    pub fn set_contrast(&mut self, contrast: u8) -> Result<(), Error> {
         self.properties.set_contrast(contrast)
     }
+
+    /// Enable or disable zoom in mode. While enabled, the drawing area is half as high and every
+    /// row is shown twice as high on the display.
+    pub fn set_zoom(&mut self, zoom: bool) -> Result<(), Error> {
+        self.invalidate_flush_jobs();
+        self.properties.set_zoom(zoom)
+    }
+
+    /// Enable or disable the command lock. See [DisplayProperties::set_command_lock] for details.
+    pub fn set_command_lock(&mut self, lock: bool) -> Result<(), Error> {
+        self.properties.set_command_lock(lock)
+    }
+
+    /// Set the fade out or blinking mode. See [DisplayProperties::set_fade] for details.
+    pub fn set_fade(&mut self, mode: FadeMode, interval: u8) -> Result<(), Error> {
+        self.properties.set_fade(mode, interval)
+    }
+
+    /// Configure a continuous horizontal scroll. See
+    /// [DisplayProperties::set_horizontal_scroll] for details.
+    pub fn set_horizontal_scroll(
+        &mut self,
+        direction: ScrollDirection,
+        pages: (Page, Page),
+        columns: (u8, u8),
+        interval: NFrames,
+    ) -> Result<(), Error> {
+        self.mark_scrolled();
+        self.properties
+            .set_horizontal_scroll(direction, pages, columns, interval)
+    }
+
+    /// Configure a continuous vertical scroll, optionally combined with a horizontal scroll. See
+    /// [DisplayProperties::set_vertical_scroll] for details.
+    pub fn set_vertical_scroll(
+        &mut self,
+        horizontal: Option<ScrollDirection>,
+        pages: (Page, Page),
+        columns: (u8, u8),
+        vertical_offset: u8,
+        interval: NFrames,
+    ) -> Result<(), Error> {
+        self.mark_scrolled();
+        self.properties
+            .set_vertical_scroll(horizontal, pages, columns, vertical_offset, interval)
+    }
+
+    /// Set the area used by vertical scrolling. See
+    /// [DisplayProperties::set_vertical_scroll_area] for details.
+    pub fn set_vertical_scroll_area(
+        &mut self,
+        fixed_rows: u8,
+        scroll_rows: u8,
+    ) -> Result<(), Error> {
+        self.properties
+            .set_vertical_scroll_area(fixed_rows, scroll_rows)
+    }
+
+    /// Start the configured scroll
+    pub fn start_scroll(&mut self) -> Result<(), Error> {
+        self.invalidate_flush_jobs();
+        self.properties.start_scroll()
+    }
+
+    /// Advance a [Ticker] by one column. Only the ticker area of the display is updated.
+    ///
+    /// The ticker owns its area until `stop_ticker` is called or another ticker is stepped:
+    /// `flush` and [FlushJob]s leave the area out, so that the rest of the display can be updated
+    /// while the ticker runs. `flush_all` and `flush_region` still overwrite it.
+    pub fn step_ticker(&mut self, ticker: &mut Ticker) -> Result<(), Error> {
+        // A rejected ticker doesn't take over its area
+        ticker.source_width(&self.properties)?;
+        self.mark_scrolled();
+
+        let ((start_page, end_page), (start_column, end_column)) = ticker.area();
+        let (display_width, _) = self.properties.get_size().dimensions();
+        let area = (
+            (start_page as usize, end_page as usize + 1),
+            (
+                start_column,
+                end_column.min(display_width.saturating_sub(1)),
+            ),
+        );
+
+        if self.ticker_area != Some(area) {
+            self.stop_ticker();
+            self.ticker_area = Some(area);
+        }
+
+        self.invalidate_flush_jobs();
+        ticker.step(&mut self.properties)
+    }
+
+    /// Hand the area of the ticker last stepped by `step_ticker` back to the display buffer. The
+    /// next `flush` overwrites the area with the content of the buffer.
+    pub fn stop_ticker(&mut self) {
+        if let Some(((start_page, end_page), columns)) = self.ticker_area.take() {
+            if columns.0 <= columns.1 {
+                for page in start_page..end_page {
+                    self.mark_dirty(page, columns);
+                }
+            }
+
+            self.invalidate_flush_jobs();
+        }
+    }
+
+    /// Stop any active scroll. The whole buffer is sent by the next flush to restore the display
+    /// content.
+    pub fn stop_scroll(&mut self) -> Result<(), Error> {
+        self.mark_all_dirty();
+        self.properties.stop_scroll()
+    }
 }
 
 #[cfg(feature = "graphics")]
@@ -184,16 +777,74 @@ use embedded_graphics_core::{
     draw_target::DrawTarget,
     geometry::{Dimensions, OriginDimensions, Size},
     pixelcolor::BinaryColor,
+    primitives::Rectangle,
     Pixel,
 };
 
 #[cfg(feature = "graphics")]
-impl<DI> DrawTarget for GraphicsMode<DI>
+impl<DI, SIZE, BUF> GraphicsMode<DI, SIZE, BUF>
+where
+    DI: WriteOnlyDataCommand,
+    SIZE: PanelSize,
+    BUF: AsMut<[u8]> + AsRef<[u8]>,
+{
+    /// Write out the part of the buffer covered by `area` to the display, regardless of which
+    /// parts changed since the last flush. The area is extended to whole pages of the display
+    /// RAM, taking into account the display rotation. An active scroll is stopped while the
+    /// display RAM is rewritten and restarted afterwards. As the scroll moved the content of the
+    /// display RAM, the whole buffer is sent by the next `flush` with changes in that case.
+    pub fn flush_region(&mut self, area: Rectangle) -> Result<(), Error> {
+        self.check_geometry(Operation::Flush)?;
+
+        let (columns, pages) = match self.region_window(area) {
+            Some(window) => window,
+            None => return Ok(()),
+        };
+
+        let scrolling = self.properties.is_scrolling();
+
+        self.mark_scrolled();
+        self.flush_window(columns, pages)?;
+
+        if scrolling {
+            self.properties.start_scroll()?;
+        }
+
+        Ok(())
+    }
+
+    /// Get the window of columns and pages covering `area`, or `None` if it lies outside of the
+    /// display
+    pub(crate) fn region_window(&self, area: Rectangle) -> Option<((u8, u8), (usize, usize))> {
+        let area = area.intersection(&self.bounding_box());
+        let bottom_right = area.bottom_right()?;
+
+        let (start_column, start_row) =
+            self.to_physical(area.top_left.x as u32, area.top_left.y as u32);
+        let (end_column, end_row) = self.to_physical(bottom_right.x as u32, bottom_right.y as u32);
+
+        let columns = (
+            start_column.min(end_column) as u8,
+            start_column.max(end_column) as u8,
+        );
+        let pages = (
+            start_row.min(end_row) as usize / 8,
+            start_row.max(end_row) as usize / 8 + 1,
+        );
+
+        Some((columns, pages))
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl<DI, SIZE, BUF> DrawTarget for GraphicsMode<DI, SIZE, BUF>
 where
     DI: WriteOnlyDataCommand,
+    SIZE: PanelSize,
+    BUF: AsMut<[u8]> + AsRef<[u8]>,
 {
     type Color = BinaryColor;
-    type Error = DisplayError;
+    type Error = Error;
 
     fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
     where
@@ -201,7 +852,8 @@ where
     {
         let bb = self.bounding_box();
 
-        pixels.into_iter()
+        pixels
+            .into_iter()
             .filter(|Pixel(pos, _color)| bb.contains(*pos))
             .for_each(|Pixel(pos, color)| {
                 self.set_pixel(pos.x as u32, pos.y as u32, color.is_on().into())
@@ -212,9 +864,11 @@ where
 }
 
 #[cfg(feature = "graphics")]
-impl<DI> OriginDimensions for GraphicsMode<DI>
+impl<DI, SIZE, BUF> OriginDimensions for GraphicsMode<DI, SIZE, BUF>
 where
     DI: WriteOnlyDataCommand,
+    SIZE: PanelSize,
+    BUF: AsMut<[u8]> + AsRef<[u8]>,
 {
     fn size(&self) -> Size {
         let (w, h) = self.get_dimensions();
@@ -222,3 +876,446 @@ where
         Size::new(w.into(), h.into())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use display_interface::{DataFormat, WriteOnlyDataCommand};
+
+    use super::GraphicsMode;
+    use crate::{
+        builder::Builder,
+        command::{AddrMode, NFrames, Page, ScrollDirection},
+        config::InitSequence,
+        displayrotation::DisplayRotation,
+        displaysize::{CustomSize, DisplaySize128x32, DisplaySize96x16, PanelSize},
+        error::{Error, Operation},
+        test_helpers::{command, initialised_display, RecordingInterface},
+        ticker::Ticker,
+    };
+
+    /// Initialised display whose buffer has been sent, with an empty log
+    fn flushed_display() -> GraphicsMode<RecordingInterface> {
+        let mut display = initialised_display();
+        display.flush().unwrap();
+        display.properties_mut().iface_mut().len = 0;
+
+        display
+    }
+
+    fn take_recorded<SIZE, BUF>(
+        display: &mut GraphicsMode<RecordingInterface, SIZE, BUF>,
+    ) -> ([u16; 4096], usize)
+    where
+        SIZE: PanelSize,
+        BUF: AsMut<[u8]> + AsRef<[u8]>,
+    {
+        let iface = display.properties_mut().iface_mut();
+        let recorded = (iface.log, iface.len);
+        iface.len = 0;
+
+        recorded
+    }
+
+    fn data_len(recorded: &[u16]) -> usize {
+        recorded.iter().filter(|&&byte| byte < 0x100).count()
+    }
+
+    #[test]
+    fn flushes_single_pixel_windows() {
+        let mut display = flushed_display();
+
+        display.set_pixel(3, 20, 1);
+        display.set_pixel(100, 63, 1);
+        display.flush().unwrap();
+
+        let (log, len) = take_recorded(&mut display);
+        assert_eq!(
+            log[..len],
+            [
+                0x121, 0x103, 0x103, 0x122, 0x102, 0x102, 0x10, // Page 2
+                0x121, 0x164, 0x164, 0x122, 0x107, 0x107, 0x80, // Page 7
+            ]
+        );
+    }
+
+    #[test]
+    fn merges_pages_with_same_columns() {
+        let mut display = flushed_display();
+
+        for row in 8..32 {
+            display.set_pixel(5, row, 1);
+            display.set_pixel(9, row, 1);
+        }
+        display.set_pixel(7, 40, 1);
+        display.flush().unwrap();
+
+        let (log, len) = take_recorded(&mut display);
+        assert_eq!(log[..6], [0x121, 0x105, 0x109, 0x122, 0x101, 0x103]);
+        assert_eq!(log[6..11], [0xff, 0, 0, 0, 0xff]);
+        assert_eq!(log[16..21], [0xff, 0, 0, 0, 0xff]);
+        assert_eq!(
+            log[21..len],
+            [0x121, 0x107, 0x107, 0x122, 0x105, 0x105, 0x01]
+        );
+        assert_eq!(len, 28);
+
+        // Whole rows of consecutive pages are sent as a single data write
+        for column in 0..128 {
+            display.set_pixel(column, 0, 1);
+            display.set_pixel(column, 15, 1);
+        }
+        display.flush().unwrap();
+
+        let (log, len) = take_recorded(&mut display);
+        assert_eq!(log[..6], [0x121, 0x100, 0x17f, 0x122, 0x100, 0x101]);
+        assert_eq!(data_len(&log[..len]), 256);
+        assert_eq!(len, 6 + 256);
+    }
+
+    #[test]
+    fn clean_flush_sends_nothing() {
+        let mut display = flushed_display();
+
+        display.flush().unwrap();
+        display.set_pixel(3, 20, 0);
+        display.set_pixel(500, 20, 1);
+        display.flush().unwrap();
+
+        assert_eq!(take_recorded(&mut display).1, 0);
+    }
+
+    #[test]
+    fn flushes_everything_when_marked_dirty() {
+        let mut display = flushed_display();
+
+        display.mark_all_dirty();
+        display.flush().unwrap();
+
+        let (log, len) = take_recorded(&mut display);
+        assert_eq!(log[..6], [0x121, 0x100, 0x17f, 0x122, 0x100, 0x107]);
+        assert_eq!(data_len(&log[..len]), 1024);
+
+        display.set_pixel(3, 20, 1);
+        display.clear();
+        display.flush().unwrap();
+
+        let (log, len) = take_recorded(&mut display);
+        assert_eq!(data_len(&log[..len]), 1024);
+    }
+
+    #[test]
+    fn flushes_everything_after_scrolling() {
+        let mut display = flushed_display();
+        let pages = (Page::Page0, Page::Page7);
+
+        display
+            .set_horizontal_scroll(ScrollDirection::Left, pages, (0, 127), NFrames::F2)
+            .unwrap();
+        display.start_scroll().unwrap();
+        take_recorded(&mut display);
+
+        // The scroll is restarted after rewriting the display RAM
+        display.set_pixel(3, 20, 1);
+        display.flush().unwrap();
+        let (log, len) = take_recorded(&mut display);
+        assert_eq!(data_len(&log[..len]), 1024);
+        assert_eq!(log[len - 1], 0x12f);
+        assert!(display.properties().is_scrolling());
+
+        // Without changes, the scroll keeps running
+        display.flush().unwrap();
+        assert_eq!(take_recorded(&mut display).1, 0);
+        assert!(display.properties().is_scrolling());
+
+        display.set_pixel(3, 20, 0);
+        display.flush().unwrap();
+        let (log, len) = take_recorded(&mut display);
+        assert_eq!(data_len(&log[..len]), 1024);
+        assert!(display.properties().is_scrolling());
+
+        display.stop_scroll().unwrap();
+        display.flush().unwrap();
+        let (log, len) = take_recorded(&mut display);
+        assert_eq!(data_len(&log[..len]), 1024);
+        assert!(!display.properties().is_scrolling());
+    }
+
+    #[test]
+    fn leaves_ticker_area_out_of_flush() {
+        let mut display = flushed_display();
+        let text = [0x3c; 16];
+        let pages = (Page::Page6, Page::Page7);
+        let mut ticker = Ticker::new(&text, ScrollDirection::Left, pages, (8, 39));
+
+        display.step_ticker(&mut ticker).unwrap();
+        take_recorded(&mut display);
+
+        // Changes on other pages and around the ticker are sent, the ticker area is kept
+        for column in 0..128 {
+            display.set_pixel(column, 50, 1);
+        }
+        display.set_pixel(3, 20, 1);
+        display.flush().unwrap();
+
+        let (log, len) = take_recorded(&mut display);
+        assert_eq!(log[..7], [0x121, 0x103, 0x103, 0x122, 0x102, 0x102, 0x10]);
+        assert_eq!(log[7..13], [0x121, 0x100, 0x107, 0x122, 0x106, 0x106]);
+        assert_eq!(log[21..27], [0x121, 0x128, 0x17f, 0x122, 0x106, 0x106]);
+        assert_eq!(data_len(&log[..len]), 1 + 128 - 32);
+        assert_eq!(len, 7 + 6 + 8 + 6 + 88);
+
+        display.step_ticker(&mut ticker).unwrap();
+        take_recorded(&mut display);
+        display.flush().unwrap();
+        assert_eq!(take_recorded(&mut display).1, 0);
+
+        // The ticker area is restored from the buffer once the ticker stops
+        display.stop_ticker();
+        display.flush().unwrap();
+
+        let (log, len) = take_recorded(&mut display);
+        assert_eq!(log[..6], [0x121, 0x108, 0x127, 0x122, 0x106, 0x107]);
+        assert_eq!(data_len(&log[..len]), 64);
+    }
+
+    #[test]
+    fn rejected_ticker_keeps_area_in_flush() {
+        let mut display = flushed_display();
+        let pages = (Page::Page6, Page::Page7);
+
+        // The source doesn't fill whole pages, and the area is a single column
+        let text = [0x3c; 15];
+        let mut ticker = Ticker::new(&text, ScrollDirection::Left, pages, (8, 39));
+        assert!(display.step_ticker(&mut ticker).is_err());
+        let text = [0x3c; 16];
+        let mut ticker = Ticker::new(&text, ScrollDirection::Left, pages, (8, 8));
+        assert!(display.step_ticker(&mut ticker).is_err());
+        assert_eq!(take_recorded(&mut display).1, 0);
+
+        for column in 0..128 {
+            display.set_pixel(column, 50, 1);
+        }
+        display.flush().unwrap();
+
+        let (log, len) = take_recorded(&mut display);
+        assert_eq!(log[..6], [0x121, 0x100, 0x17f, 0x122, 0x106, 0x106]);
+        assert_eq!(data_len(&log[..len]), 128);
+    }
+
+    #[test]
+    fn flush_bytes_match_flush_all() {
+        let mut display = flushed_display();
+        display.set_pixel(3, 20, 1);
+        display.set_pixel(127, 63, 1);
+
+        let mut sent = RecordingInterface::new();
+        let (commands, data) = display.flush_bytes().unwrap();
+        assert_eq!(data.len(), 1024);
+        sent.send_commands(DataFormat::U8(&commands)).unwrap();
+        sent.send_data(DataFormat::U8(data)).unwrap();
+
+        // Both are sent as a single command and a single data write
+        let transfers = display.properties_mut().iface_mut().transfers;
+        display.flush_all().unwrap();
+        let iface = display.properties_mut().iface_mut();
+        assert_eq!(iface.recorded(), sent.recorded());
+        assert_eq!(iface.transfers - transfers, 2);
+        assert_eq!(sent.transfers, 2);
+        assert_eq!(
+            sent.recorded()[..6],
+            [0x121, 0x100, 0x17f, 0x122, 0x100, 0x107]
+        );
+    }
+
+    #[test]
+    fn init_switches_to_horizontal_addressing() {
+        let builder = Builder::new().with_init_sequence(InitSequence::Replace(&[0x20, 0x02, 0xAF]));
+
+        let mut properties = builder.connect(RecordingInterface::new()).release();
+        properties.init_column_mode().unwrap();
+        assert!(matches!(properties.get_addr_mode(), AddrMode::Page));
+
+        let mut display: GraphicsMode<_> = builder.connect(RecordingInterface::new()).into();
+        display.init().unwrap();
+        assert!(matches!(
+            display.properties().get_addr_mode(),
+            AddrMode::Horizontal
+        ));
+        assert_eq!(
+            display.properties_mut().iface_mut().recorded(),
+            [0x120, 0x102, 0x1af, 0x120, 0x100]
+        );
+    }
+
+    #[test]
+    fn rejects_too_small_buffer() {
+        let builder = Builder::new().with_size(CustomSize::new(128, 32));
+        let mut display: GraphicsMode<_, CustomSize, [u8; 511]> =
+            builder.connect(RecordingInterface::new()).into();
+
+        assert!(matches!(
+            display.init(),
+            Err(Error::InvalidParameter(Operation::Init))
+        ));
+        assert!(display.flush().is_err());
+        assert_eq!(display.properties_mut().iface_mut().len, 0);
+
+        let properties = builder.connect(RecordingInterface::new()).release();
+        let mut display = GraphicsMode::with_buffer(properties, [0; 512]);
+        assert!(display.init().is_ok());
+
+        let mut buffer = [0; 512];
+        let properties = Builder::new()
+            .with_size(DisplaySize128x32)
+            .connect(RecordingInterface::new())
+            .release();
+        let mut display = GraphicsMode::with_buffer(properties, &mut buffer[..511]);
+        assert!(display.init().is_err());
+        assert!(display.flush().is_err());
+    }
+
+    #[test]
+    fn column_offset_follows_segment_remap() {
+        fn check_size<SIZE: PanelSize>(size: SIZE, rotation: DisplayRotation, first_column: u8) {
+            let (width, height) = size.display_size().dimensions();
+            let builder = Builder::new().with_size(size).with_rotation(rotation);
+            let properties = builder.connect(RecordingInterface::new()).release();
+            let mut display = GraphicsMode::with_buffer(properties, [0; 1024]);
+            display.init().unwrap();
+            take_recorded(&mut display);
+
+            let last_column = first_column + width - 1;
+
+            display.flush_all().unwrap();
+            let (log, _) = take_recorded(&mut display);
+            assert_eq!(
+                log[..3],
+                [0x121, command(first_column), command(last_column)]
+            );
+            assert_eq!(log[3..6], [0x122, 0x100, command(height / 8 - 1)]);
+
+            display.set_pixel(0, 0, 1);
+            display.flush().unwrap();
+            let (log, _) = take_recorded(&mut display);
+            assert_eq!(
+                log[..3],
+                [0x121, command(first_column), command(first_column)]
+            );
+
+            let pages = (Page::Page0, Page::Page1);
+            display
+                .set_horizontal_scroll(ScrollDirection::Left, pages, (0, 9), NFrames::F2)
+                .unwrap();
+            display.start_scroll().unwrap();
+            let (log, len) = take_recorded(&mut display);
+            assert_eq!(
+                log[len - 3..len],
+                [command(first_column), command(first_column + 9), 0x12f]
+            );
+        }
+
+        let custom = CustomSize::new(100, 16).with_column_offset(10);
+        check_size(DisplaySize96x16, DisplayRotation::Rotate0, 0);
+        check_size(DisplaySize96x16, DisplayRotation::Rotate180, 32);
+        check_size(DisplaySize96x16, DisplayRotation::Rotate90, 32);
+        check_size(custom, DisplayRotation::Rotate0, 10);
+        check_size(custom, DisplayRotation::Rotate180, 18);
+        check_size(custom, DisplayRotation::Rotate270, 10);
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn zoom_halves_drawing_area() {
+        use embedded_graphics_core::{
+            geometry::{Dimensions, OriginDimensions, Point, Size},
+            primitives::Rectangle,
+        };
+
+        let mut display = flushed_display();
+
+        display.set_zoom(true).unwrap();
+        assert_eq!(display.get_dimensions(), (128, 32));
+        assert_eq!(display.size(), Size::new(128, 32));
+        assert_eq!(
+            display.bounding_box(),
+            Rectangle::new(Point::zero(), Size::new(128, 32))
+        );
+
+        // Only the visible upper half of the buffer is sent
+        display.set_pixel(3, 20, 1);
+        display.set_pixel(5, 50, 1);
+        take_recorded(&mut display);
+        display.flush().unwrap();
+
+        let (log, len) = take_recorded(&mut display);
+        assert_eq!(log[..len], [0x121, 0x103, 0x103, 0x122, 0x102, 0x102, 0x10]);
+
+        // The lower half drawn while zoomed is sent once it becomes visible again
+        display.set_zoom(false).unwrap();
+        assert_eq!(display.size(), Size::new(128, 64));
+        take_recorded(&mut display);
+        display.flush().unwrap();
+
+        let (log, len) = take_recorded(&mut display);
+        assert_eq!(log[..len], [0x121, 0x105, 0x105, 0x122, 0x106, 0x106, 0x04]);
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn maps_regions_to_pages_and_columns() {
+        use embedded_graphics_core::{
+            geometry::{Point, Size},
+            primitives::Rectangle,
+        };
+
+        let rectangle =
+            |x, y, width, height| Rectangle::new(Point::new(x, y), Size::new(width, height));
+        let mut display = flushed_display();
+
+        // Rows are extended to whole pages
+        assert_eq!(
+            display.region_window(rectangle(0, 0, 1, 8)),
+            Some(((0, 0), (0, 1)))
+        );
+        assert_eq!(
+            display.region_window(rectangle(0, 8, 1, 8)),
+            Some(((0, 0), (1, 2)))
+        );
+        assert_eq!(
+            display.region_window(rectangle(0, 7, 1, 2)),
+            Some(((0, 0), (0, 2)))
+        );
+        assert_eq!(
+            display.region_window(rectangle(120, 60, 20, 20)),
+            Some(((120, 127), (7, 8)))
+        );
+        assert_eq!(display.region_window(rectangle(128, 0, 5, 5)), None);
+
+        for &rotation in &[DisplayRotation::Rotate90, DisplayRotation::Rotate270] {
+            display.set_rotation(rotation).unwrap();
+
+            // X and Y are swapped, so X selects the pages and Y the columns
+            assert_eq!(
+                display.region_window(rectangle(10, 20, 5, 30)),
+                Some(((20, 49), (1, 2)))
+            );
+            assert_eq!(
+                display.region_window(rectangle(7, 0, 2, 1)),
+                Some(((0, 0), (0, 2)))
+            );
+            assert_eq!(
+                display.region_window(rectangle(56, 100, 20, 40)),
+                Some(((100, 127), (7, 8)))
+            );
+            assert_eq!(display.region_window(rectangle(64, 0, 5, 5)), None);
+        }
+
+        display.flush().unwrap();
+        take_recorded(&mut display);
+        display.flush_region(rectangle(10, 20, 5, 30)).unwrap();
+
+        let (log, len) = take_recorded(&mut display);
+        assert_eq!(log[..6], [0x121, 0x114, 0x131, 0x122, 0x101, 0x101]);
+        assert_eq!(data_len(&log[..len]), 30);
+    }
+}
@@ -3,8 +3,18 @@
 //! This driver can be used in different modes. A mode defines how the driver will behave, and what
 //! methods it exposes. Look at the modes below for more information on what they expose.
 
+#[cfg(feature = "async")]
+pub mod asynch;
 pub mod displaymode;
+pub mod flushjob;
 pub mod graphics;
 pub mod raw;
 
-pub use self::{graphics::GraphicsMode, raw::RawMode};
+pub use self::{
+    flushjob::{FlushJob, FlushProgress},
+    graphics::GraphicsMode,
+    raw::RawMode,
+};
+
+#[cfg(feature = "async")]
+pub use self::asynch::AsyncGraphicsMode;
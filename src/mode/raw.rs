@@ -7,35 +7,61 @@
 use display_interface::WriteOnlyDataCommand;
 
 use crate::{
-    mode::displaymode::DisplayModeTrait, properties::DisplayProperties,
+    command::{Command, FadeMode},
+    displaysize::{DisplaySize128x64, PanelSize},
+    error::Error,
+    mode::displaymode::DisplayModeTrait,
+    properties::DisplayProperties,
 };
 
 /// Raw display mode
-pub struct RawMode<DI>
+pub struct RawMode<DI, SIZE = DisplaySize128x64>
 where
     DI: WriteOnlyDataCommand,
+    SIZE: PanelSize,
 {
-    properties: DisplayProperties<DI>,
+    properties: DisplayProperties<DI, SIZE>,
 }
 
-impl<DI> DisplayModeTrait<DI> for RawMode<DI>
+impl<DI, SIZE> DisplayModeTrait<DI, SIZE> for RawMode<DI, SIZE>
 where
     DI: WriteOnlyDataCommand,
+    SIZE: PanelSize,
 {
     /// Create new RawMode instance
-    fn new(properties: DisplayProperties<DI>) -> Self {
+    fn new(properties: DisplayProperties<DI, SIZE>) -> Self {
         RawMode { properties }
     }
 
     /// Release all resources used by RawMode
-    fn release(self) -> DisplayProperties<DI> {
+    fn release(self) -> DisplayProperties<DI, SIZE> {
         self.properties
     }
 }
 
-impl<DI: WriteOnlyDataCommand> RawMode<DI> {
+impl<DI: WriteOnlyDataCommand, SIZE: PanelSize> RawMode<DI, SIZE> {
     /// Create a new raw display mode
-    pub fn new(properties: DisplayProperties<DI>) -> Self {
+    pub fn new(properties: DisplayProperties<DI, SIZE>) -> Self {
         RawMode { properties }
     }
+
+    /// Enable or disable zoom in mode. See [DisplayProperties::set_zoom] for details.
+    pub fn set_zoom(&mut self, zoom: bool) -> Result<(), Error> {
+        self.properties.set_zoom(zoom)
+    }
+
+    /// Enable or disable the command lock. See [DisplayProperties::set_command_lock] for details.
+    pub fn set_command_lock(&mut self, lock: bool) -> Result<(), Error> {
+        self.properties.set_command_lock(lock)
+    }
+
+    /// Set the fade out or blinking mode. See [DisplayProperties::set_fade] for details.
+    pub fn set_fade(&mut self, mode: FadeMode, interval: u8) -> Result<(), Error> {
+        self.properties.set_fade(mode, interval)
+    }
+
+    /// Send a low-level command. See [DisplayProperties::send_command] for details.
+    pub fn send_command(&mut self, command: Command) -> Result<(), Error> {
+        self.properties.send_command(command)
+    }
 }
@@ -1,7 +1,17 @@
 //! Crate prelude
 
 pub use super::{
+    command::{AddrMode, FadeMode, NFrames, Page, ScrollDirection, VcomhLevel},
+    config::InitSequence,
     displayrotation::DisplayRotation,
-    displaysize::DisplaySize,
-    mode::GraphicsMode,
+    displaysize::{
+        CustomSize, DisplaySize, DisplaySize128x32, DisplaySize128x64, DisplaySize64x48,
+        DisplaySize72x40, DisplaySize96x16, PanelSize,
+    },
+    error::{Error, Operation},
+    mode::{FlushJob, FlushProgress, GraphicsMode},
+    wiring::Wiring,
 };
+
+#[cfg(feature = "async")]
+pub use super::mode::AsyncGraphicsMode;
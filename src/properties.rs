@@ -1,110 +1,316 @@
 //! Container to store and set display properties
 
+use core::marker::PhantomData;
+
 use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
 
 use crate::{
-    command::Command, displayrotation::DisplayRotation, displaysize::DisplaySize,
+    command::{AddrMode, Command, FadeMode, NFrames, Page, ScrollDirection},
+    config::{InitConfig, InitSequence},
+    displayrotation::DisplayRotation,
+    displaysize::{DisplaySize, DisplaySize128x64, PanelSize},
+    error::{Error, Operation},
 };
 
 /// Display properties struct
-pub struct DisplayProperties<DI> {
+#[derive(Clone)]
+pub struct DisplayProperties<DI, SIZE = DisplaySize128x64> {
     iface: DI,
     display_size: DisplaySize,
+    size: PhantomData<SIZE>,
     display_rotation: DisplayRotation,
+    mirror: (bool, bool),
+    config: InitConfig,
+    addr_mode: AddrMode,
+    contrast: u8,
     draw_area_start: (u8, u8),
     draw_area_end: (u8, u8),
     draw_column: u8,
     draw_row: u8,
+    draw_address_pending: bool,
+    scroll_setup: Option<Command>,
+    scroll_active: bool,
+    vertical_scroll_area: (u8, u8),
+    zoom: bool,
+    command_lock: bool,
 }
 
-impl<DI> DisplayProperties<DI>
+impl<DI, SIZE> DisplayProperties<DI, SIZE>
 where
     DI: WriteOnlyDataCommand,
+    SIZE: PanelSize,
 {
     /// Create new DisplayProperties instance
     pub fn new(
         iface: DI,
-        display_size: DisplaySize,
+        size: SIZE,
         display_rotation: DisplayRotation,
-    ) -> DisplayProperties<DI> {
+    ) -> DisplayProperties<DI, SIZE> {
+        let display_size = size.display_size();
+
         DisplayProperties {
             iface,
             display_size,
+            size: PhantomData,
             display_rotation,
+            mirror: (false, false),
+            config: InitConfig::default(),
+            addr_mode: AddrMode::Page,
+            contrast: 0x7f,
             draw_area_start: (0, 0),
             draw_area_end: (0, 0),
             draw_column: 0,
             draw_row: 0,
+            draw_address_pending: false,
+            scroll_setup: None,
+            scroll_active: false,
+            vertical_scroll_area: (0, display_size.multiplex()),
+            zoom: false,
+            command_lock: false,
         }
     }
 
+    /// Set the initial mirroring of the display
+    pub(crate) fn with_mirror(self, mirror: (bool, bool)) -> Self {
+        DisplayProperties { mirror, ..self }
+    }
+
+    /// Replace the init-time parameters sent by `init_column_mode`
+    pub(crate) fn with_config(self, config: InitConfig) -> Self {
+        let mut props = DisplayProperties { config, ..self };
+        props.vertical_scroll_area = (0, props.multiplex());
+
+        props
+    }
+
     /// Initialise the display in column mode (i.e. a byte walks down a column of 8 pixels) with
     /// column 0 on the left and column _(display_width - 1)_ on the right.
-    pub fn init_column_mode(&mut self) -> Result<(), DisplayError> {
-        let display_rotation = self.display_rotation;
-
-        Command::DisplayClockDiv(0xa, 0x0).send(&mut self.iface)?;
+    ///
+    /// The init-time parameters set up through the [Builder](crate::Builder) are sent as well.
+    /// Fails with `Error::InvalidParameter` if the display size or any of the parameters is not
+    /// valid.
+    ///
+    /// A custom [InitSequence] is sent instead of or after the built-in sequence. The addressing
+    /// mode, rotation, contrast, zoom, scroll state, multiplex ratio, start line and command lock
+    /// it sets up are tracked by the driver.
+    pub fn init_column_mode(&mut self) -> Result<(), Error> {
+        self.init_with(|iface, sequence| iface.send_commands(DataFormat::U8(sequence)))
+    }
 
-        self.set_rotation(display_rotation)?;
+    /// Validate the parameters and send the init sequence, handing any custom sequence to
+    /// `send_sequence` instead of sending it through the interface
+    pub(crate) fn init_with<F>(&mut self, send_sequence: F) -> Result<(), Error>
+    where
+        F: FnOnce(&mut DI, &'static [u8]) -> Result<(), DisplayError>,
+    {
+        let (_, display_height) = self.display_size.dimensions();
+        let builtin = !matches!(self.config.sequence, Some(InitSequence::Replace(_)));
 
-        Command::Contrast(0x6f).send(&mut self.iface)?;
-        Command::PreChargePeriod(0x3, 0xd).send(&mut self.iface)?;
-        Command::ScrollActive(false).send(&mut self.iface)?;
-        Command::DisplayOn(true).send(&mut self.iface)?;
+        if !self.display_size.is_valid()
+            || builtin && (!self.config.is_valid() || display_height > self.multiplex())
+            || self
+                .config
+                .sequence
+                .is_some_and(|sequence| !sequence.is_valid())
+        {
+            return Err(Error::InvalidParameter(Operation::Init));
+        }
 
-        Ok(())
+        self.unlocked(Operation::Init, |props| match props.config.sequence {
+            Some(InitSequence::Replace(sequence)) => props.send_sequence(sequence, send_sequence),
+            Some(InitSequence::Append(sequence)) => {
+                props.send_init_sequence()?;
+                props.send_sequence(sequence, send_sequence)
+            }
+            None => props.send_init_sequence(),
+        })
     }
 
     /// Set the position in the framebuffer of the display where any sent data should be
     /// drawn. This method can be used for changing the affected area on the screen as well
     /// as (re-)setting the start point of the next `draw` call.
-    pub fn set_draw_area(&mut self, start: (u8, u8), end: (u8, u8)) -> Result<(), DisplayError> {
-        self.draw_area_start = start;
-        self.draw_area_end = end;
-        self.draw_column = start.0;
-        self.draw_row = start.1;
+    ///
+    /// In horizontal and vertical addressing mode, the area is programmed into the controller as
+    /// a window, so that data for the whole area can be sent in a single `draw` call without any
+    /// further address commands.
+    ///
+    /// The area must not be empty and must lie within the 128 columns and 64 rows of the display
+    /// RAM, otherwise `Error::InvalidParameter` is returned. Data is drawn to whole pages, so in
+    /// every addressing mode the area covers the pages containing its first and last row.
+    ///
+    /// The datasheet requires scrolling to be deactivated before the display RAM is rewritten, so
+    /// any active scroll is stopped by this method.
+    pub fn set_draw_area(&mut self, start: (u8, u8), end: (u8, u8)) -> Result<(), Error> {
+        if !Self::is_valid_area(start, end) {
+            return Err(Error::InvalidParameter(Operation::SetDrawArea));
+        }
+
+        self.unlocked(Operation::SetDrawArea, |props| {
+            props.send_stop_scroll()?;
+
+            props.draw_area_start = start;
+            props.draw_area_end = end;
+            props.draw_column = start.0;
+            props.draw_row = start.1;
+            props.draw_address_pending = false;
 
-        self.send_draw_address()
+            match props.addr_mode {
+                AddrMode::Page => props.send_draw_address(),
+                AddrMode::Horizontal | AddrMode::Vertical => {
+                    let window = Self::window_commands(start, end);
+                    props.iface.send_commands(DataFormat::U8(&window))
+                }
+            }
+        })
     }
 
     /// Send the data to the display for drawing at the current position in the framebuffer
     /// and advance the position accordingly. Cf. `set_draw_area` to modify the affected area by
     /// this method.
-    pub fn draw(&mut self, mut buffer: &[u8]) -> Result<(), DisplayError> {
-        while !buffer.is_empty() {
-            let count = self.draw_area_end.0 - self.draw_column;
-            self.iface.send_data(DataFormat::U8(&buffer[..count as usize]))?;
-            self.draw_column += count;
-
-            if self.draw_column >= self.draw_area_end.0 {
-                self.draw_column = self.draw_area_start.0;
-
-                self.draw_row += 8;
-                if self.draw_row >= self.draw_area_end.1 {
-                    self.draw_row = self.draw_area_start.1;
+    ///
+    /// In page and horizontal addressing mode, the data fills the draw area row by row. In
+    /// vertical addressing mode, it fills the draw area column by column. Fails with
+    /// `Error::InvalidParameter` if no draw area has been set.
+    ///
+    /// The data can be streamed in chunks of any size across several calls. The position wraps
+    /// at the edges of the draw area, and in page addressing mode the address of the next page is
+    /// only sent once data for it follows.
+    pub fn draw(&mut self, mut buffer: &[u8]) -> Result<(), Error> {
+        if !Self::is_valid_area(self.draw_area_start, self.draw_area_end) {
+            return Err(Error::InvalidParameter(Operation::Draw));
+        }
+
+        self.unlocked(Operation::Draw, |props| {
+            if let AddrMode::Horizontal | AddrMode::Vertical = props.addr_mode {
+                // The controller wraps around within the window by itself
+                props.iface.send_data(DataFormat::U8(buffer))?;
+                props.advance_draw_window(buffer.len());
+
+                return Ok(());
+            }
+
+            while !buffer.is_empty() {
+                if props.draw_address_pending {
+                    props.send_draw_address()?;
+                    props.draw_address_pending = false;
                 }
 
-                self.send_draw_address()?;
+                let remaining = (props.draw_area_end.0 - props.draw_column) as usize;
+                let count = remaining.min(buffer.len());
+                props.iface.send_data(DataFormat::U8(&buffer[..count]))?;
+                props.draw_column += count as u8;
+
+                if props.draw_column >= props.draw_area_end.0 {
+                    props.draw_column = props.draw_area_start.0;
+
+                    // Continue on the next page, so that rows which don't start a page cover the
+                    // same pages as the window of the other addressing modes
+                    props.draw_row = (props.draw_row / 8 + 1) * 8;
+                    if props.draw_row >= props.draw_area_end.1 {
+                        props.draw_row = props.draw_area_start.1;
+                    }
+
+                    props.draw_address_pending = true;
+                }
+
+                buffer = &buffer[count..];
             }
 
-            buffer = &buffer[count as usize..];
-        }
+            Ok(())
+        })
+    }
 
-        Ok(())
+    /// Get the command bytes which program the draw area from `start` to `end` as the window used
+    /// by horizontal and vertical addressing mode. `set_draw_area` sends these bytes in a single
+    /// transaction.
+    pub fn window_commands(start: (u8, u8), end: (u8, u8)) -> [u8; 6] {
+        let (column_range, _) = Command::ColumnRange(start.0, end.0.saturating_sub(1)).encode();
+        let (page_range, _) = Command::PageRange(
+            Page::containing(start.1),
+            Page::containing(end.1.saturating_sub(1)),
+        )
+        .encode();
+
+        [
+            column_range[0],
+            column_range[1],
+            column_range[2],
+            page_range[0],
+            page_range[1],
+            page_range[2],
+        ]
+    }
+
+    /// Whether a draw area is non-empty and lies within the display RAM
+    fn is_valid_area(start: (u8, u8), end: (u8, u8)) -> bool {
+        start.0 < end.0 && end.0 <= 128 && start.1 < end.1 && end.1 <= 64
     }
 
     fn send_draw_address(&mut self) -> Result<(), DisplayError> {
-        Command::PageAddress(self.draw_row.into()).send(&mut self.iface)?;
+        Command::PageAddress(Page::containing(self.draw_row)).send(&mut self.iface)?;
         Command::ColumnAddressLow(0xF & self.draw_column).send(&mut self.iface)?;
         Command::ColumnAddressHigh(0xF & (self.draw_column >> 4)).send(&mut self.iface)
     }
 
+    /// Advance the draw position by `count` bytes within the hardware window, following the
+    /// controller's horizontal or vertical addressing mode
+    fn advance_draw_window(&mut self, count: usize) {
+        let first_page = self.draw_area_start.1 / 8;
+        let columns = self.draw_area_end.0.saturating_sub(self.draw_area_start.0) as usize;
+        let pages =
+            (self.draw_area_end.1.saturating_sub(1) / 8).saturating_sub(first_page) as usize + 1;
+
+        if columns == 0 {
+            return;
+        }
+
+        let column = (self.draw_column - self.draw_area_start.0) as usize;
+        let page = (self.draw_row / 8 - first_page) as usize;
+
+        let (column, page) = match self.addr_mode {
+            AddrMode::Vertical => {
+                let position = (column * pages + page + count) % (columns * pages);
+                (position / pages, position % pages)
+            }
+            AddrMode::Horizontal | AddrMode::Page => {
+                let position = (page * columns + column + count) % (columns * pages);
+                (position % columns, position / columns)
+            }
+        };
+
+        self.draw_column = self.draw_area_start.0 + column as u8;
+        self.draw_row = (first_page + page as u8) * 8;
+    }
+
+    /// Get the interface the display is connected to
+    #[cfg(any(test, feature = "async"))]
+    pub(crate) fn iface_mut(&mut self) -> &mut DI {
+        &mut self.iface
+    }
+
     /// Get the configured display size
     pub fn get_size(&self) -> DisplaySize {
         self.display_size
     }
 
-    /// Get display dimensions, taking into account the current rotation of the display
+    /// Get the first display RAM column connected to the panel. The column offset of the display
+    /// size applies to the segment remap of the panel wiring. While the current orientation
+    /// flips the segment remap, the panel is connected to the columns at the other end of the
+    /// display RAM.
+    pub fn column_offset(&self) -> u8 {
+        let offset = self.display_size.column_offset();
+        let (width, _) = self.display_size.dimensions();
+        let (remap, _) = self.orientation_bits();
+
+        if remap {
+            offset
+        } else {
+            128u8.saturating_sub(width).saturating_sub(offset)
+        }
+    }
+
+    /// Get display dimensions, taking into account the current rotation of the display. While zoom
+    /// is enabled, only half of the rows of the display are available.
     ///
     /// ```rust
     ///# #[path = "test_helpers.rs"]
@@ -121,7 +327,7 @@ where
     /// assert_eq!(rotated_display.get_dimensions(), (64, 128));
     /// ```
     pub fn get_dimensions(&self) -> (u8, u8) {
-        let (w, h) = self.display_size.dimensions();
+        let (w, h) = self.get_visible_size();
 
         match self.display_rotation {
             DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => (w, h),
@@ -129,43 +335,989 @@ where
         }
     }
 
+    /// Get the size of the display RAM area that is visible on the display, without taking into
+    /// account the rotation. While zoom is enabled, only the upper half of the rows is visible.
+    pub fn get_visible_size(&self) -> (u8, u8) {
+        let (w, h) = self.display_size.dimensions();
+
+        if self.zoom {
+            (w, h / 2)
+        } else {
+            (w, h)
+        }
+    }
+
+    /// Get the GDDRAM addressing mode
+    pub fn get_addr_mode(&self) -> AddrMode {
+        self.addr_mode
+    }
+
+    /// Set the GDDRAM addressing mode used by `set_draw_area` and `draw`
+    pub fn set_addr_mode(&mut self, addr_mode: AddrMode) -> Result<(), Error> {
+        self.unlocked(Operation::SetAddrMode, |props| {
+            Command::AddressMode(addr_mode).send(&mut props.iface)?;
+            props.addr_mode = addr_mode;
+
+            Ok(())
+        })
+    }
+
     /// Get the display rotation
     pub fn get_rotation(&self) -> DisplayRotation {
         self.display_rotation
     }
 
-    /// Set the display rotation
-    pub fn set_rotation(&mut self, display_rotation: DisplayRotation) -> Result<(), DisplayError> {
-        self.display_rotation = display_rotation;
+    /// Set the display rotation. The rotation is applied on top of the segment remap and COM scan
+    /// direction of the panel wiring.
+    ///
+    /// The controller only applies the new orientation to data written afterwards, so the
+    /// display content needs to be sent again.
+    pub fn set_rotation(&mut self, display_rotation: DisplayRotation) -> Result<(), Error> {
+        let mirror = self.mirror;
+        self.unlocked(Operation::SetOrientation, |props| {
+            props.send_orientation(display_rotation, mirror)
+        })
+    }
+
+    /// Get the horizontal and vertical mirroring of the display
+    pub fn get_mirror(&self) -> (bool, bool) {
+        self.mirror
+    }
+
+    /// Mirror the display horizontally and/or vertically, e.g. for a display viewed through a
+    /// reflection. Mirroring is applied after the rotation, i.e. horizontal mirroring always
+    /// swaps left and right as seen by the viewer. Together with the four rotations, this allows
+    /// for all eight orientations of the display.
+    ///
+    /// The controller only applies the new orientation to data written afterwards, so the
+    /// display content needs to be sent again.
+    pub fn set_mirror(&mut self, horizontal: bool, vertical: bool) -> Result<(), Error> {
+        let rotation = self.display_rotation;
+        self.unlocked(Operation::SetOrientation, |props| {
+            props.send_orientation(rotation, (horizontal, vertical))
+        })
+    }
+
+    /// Turn the display on or off. The display can be drawn to and retains all
+    /// of its memory even while off.
+    pub fn display_on(&mut self, on: bool) -> Result<(), Error> {
+        self.unlocked(Operation::DisplayOn, |props| {
+            Command::DisplayOn(on).send(&mut props.iface)
+        })
+    }
+
+    /// Set the display contrast
+    pub fn set_contrast(&mut self, contrast: u8) -> Result<(), Error> {
+        self.unlocked(Operation::SetContrast, |props| {
+            props.send_contrast(contrast)
+        })
+    }
+
+    /// Get the display contrast
+    pub fn get_contrast(&self) -> u8 {
+        self.contrast
+    }
+
+    /// Enable or disable zoom in mode. While enabled, every row of the upper half of the display
+    /// RAM is shown twice as high, filling the whole display.
+    pub fn set_zoom(&mut self, zoom: bool) -> Result<(), Error> {
+        self.unlocked(Operation::SetZoom, |props| {
+            Command::Zoom(zoom).send(&mut props.iface)?;
+            props.zoom = zoom;
+
+            Ok(())
+        })
+    }
+
+    /// Whether zoom in mode is enabled
+    pub fn is_zoomed(&self) -> bool {
+        self.zoom
+    }
+
+    /// Set the fade out or blinking mode. The brightness changes by one step every
+    /// `8 * (interval + 1)` frames, where `interval` ranges from 0 to 15.
+    pub fn set_fade(&mut self, mode: FadeMode, interval: u8) -> Result<(), Error> {
+        if interval > 0xF {
+            return Err(Error::InvalidParameter(Operation::SetFade));
+        }
+
+        self.unlocked(Operation::SetFade, |props| {
+            Command::Fade(mode, interval).send(&mut props.iface)
+        })
+    }
+
+    /// Configure a continuous horizontal scroll of the given inclusive page and column ranges,
+    /// moving by one column every `interval` frames. Columns are counted from the left edge of the
+    /// panel and are not affected by the display rotation.
+    ///
+    /// Any active scroll is stopped first. Call `start_scroll` to start the new scroll.
+    pub fn set_horizontal_scroll(
+        &mut self,
+        direction: ScrollDirection,
+        pages: (Page, Page),
+        columns: (u8, u8),
+        interval: NFrames,
+    ) -> Result<(), Error> {
+        let (start_column, end_column) = self
+            .scroll_columns(pages, columns)
+            .ok_or(Error::InvalidParameter(Operation::ScrollSetup))?;
+
+        self.unlocked(Operation::ScrollSetup, Self::send_stop_scroll)?;
+        self.scroll_setup = Some(Command::HorizontalScrollSetup(
+            direction,
+            pages.0,
+            pages.1,
+            interval,
+            start_column,
+            end_column,
+        ));
+
+        Ok(())
+    }
+
+    /// Configure a continuous vertical scroll of the vertical scroll area, moving up by
+    /// `vertical_offset` rows every `interval` frames. If `horizontal` is given, the given
+    /// inclusive page and column ranges also scroll horizontally by one column per step.
+    ///
+    /// `vertical_offset` must be smaller than the number of rows in the vertical scroll area.
+    ///
+    /// Any active scroll is stopped first. Call `start_scroll` to start the new scroll.
+    pub fn set_vertical_scroll(
+        &mut self,
+        horizontal: Option<ScrollDirection>,
+        pages: (Page, Page),
+        columns: (u8, u8),
+        vertical_offset: u8,
+        interval: NFrames,
+    ) -> Result<(), Error> {
+        let (start_column, end_column) = self
+            .scroll_columns(pages, columns)
+            .ok_or(Error::InvalidParameter(Operation::ScrollSetup))?;
+
+        if vertical_offset >= self.vertical_scroll_area.1 {
+            return Err(Error::InvalidParameter(Operation::ScrollSetup));
+        }
+
+        self.unlocked(Operation::ScrollSetup, Self::send_stop_scroll)?;
+        self.scroll_setup = Some(Command::VerticalScrollSetup(
+            horizontal,
+            pages.0,
+            pages.1,
+            interval,
+            vertical_offset,
+            start_column,
+            end_column,
+        ));
+
+        Ok(())
+    }
+
+    /// Set the area used by vertical scrolling. The top `fixed_rows` rows of the display stay in
+    /// place while the `scroll_rows` rows below them scroll. Their sum must not exceed the
+    /// multiplex ratio of the display, and the display start line must be smaller than
+    /// `scroll_rows`.
+    ///
+    /// The new area is applied by the next call to `start_scroll`.
+    pub fn set_vertical_scroll_area(
+        &mut self,
+        fixed_rows: u8,
+        scroll_rows: u8,
+    ) -> Result<(), Error> {
+        let multiplex = self.multiplex();
+
+        if fixed_rows as u16 + scroll_rows as u16 > multiplex as u16
+            || self.config.start_line >= scroll_rows
+        {
+            return Err(Error::InvalidParameter(Operation::ScrollSetup));
+        }
 
-        match display_rotation {
-            DisplayRotation::Rotate0 => {
-                Command::SegmentRemap(true).send(&mut self.iface)?;
-                Command::ReverseComDir(true).send(&mut self.iface)
+        if let Some(Command::VerticalScrollSetup(.., vertical_offset, _, _)) = self.scroll_setup {
+            if vertical_offset >= scroll_rows {
+                return Err(Error::InvalidParameter(Operation::ScrollSetup));
             }
-            DisplayRotation::Rotate90 => {
-                Command::SegmentRemap(false).send(&mut self.iface)?;
-                Command::ReverseComDir(true).send(&mut self.iface)
+        }
+
+        self.vertical_scroll_area = (fixed_rows, scroll_rows);
+
+        Ok(())
+    }
+
+    /// Shift the content of the given inclusive page and column ranges by one column. The column
+    /// that is revealed keeps its previous content and can be redrawn using `set_draw_area` and
+    /// `draw`. Columns are counted from the left edge of the panel and are not affected by the
+    /// display rotation.
+    ///
+    /// The datasheet requires at least two frames between consecutive calls. Any active continuous
+    /// scroll is stopped first.
+    pub fn scroll_content(
+        &mut self,
+        direction: ScrollDirection,
+        pages: (Page, Page),
+        columns: (u8, u8),
+    ) -> Result<(), Error> {
+        let (start_column, end_column) = self
+            .content_scroll_columns(pages, columns)
+            .ok_or(Error::InvalidParameter(Operation::ScrollContent))?;
+
+        self.unlocked(Operation::ScrollContent, |props| {
+            props.send_stop_scroll()?;
+            Command::ContentScroll(direction, pages.0, pages.1, start_column, end_column)
+                .send(&mut props.iface)
+        })
+    }
+
+    /// Validate a window for `scroll_content`, which must span more than one column, and
+    /// translate its columns to display RAM columns
+    pub(crate) fn content_scroll_columns(
+        &self,
+        pages: (Page, Page),
+        columns: (u8, u8),
+    ) -> Option<(u8, u8)> {
+        self.scroll_columns(pages, columns)
+            .filter(|(start_column, end_column)| start_column != end_column)
+    }
+
+    /// Validate a scroll window and translate its columns to display RAM columns
+    fn scroll_columns(&self, pages: (Page, Page), columns: (u8, u8)) -> Option<(u8, u8)> {
+        let (start_page, end_page) = pages;
+        let (start_column, end_column) = columns;
+        let (display_width, _) = self.display_size.dimensions();
+
+        if !self.display_size.is_valid()
+            || (end_page as u8) < (start_page as u8)
+            || end_column < start_column
+            || end_column >= display_width
+        {
+            return None;
+        }
+
+        let column_offset = self.column_offset();
+
+        Some((start_column + column_offset, end_column + column_offset))
+    }
+
+    /// Start the scroll configured by `set_horizontal_scroll` or `set_vertical_scroll`. Does
+    /// nothing if no scroll has been configured.
+    ///
+    /// A vertical scroll fails with `Error::InvalidParameter` if the display start line isn't
+    /// smaller than the number of rows in the vertical scroll area, e.g. after an init sequence
+    /// moved the start line.
+    pub fn start_scroll(&mut self) -> Result<(), Error> {
+        if let Some(Command::VerticalScrollSetup(..)) = self.scroll_setup {
+            if self.config.start_line >= self.vertical_scroll_area.1 {
+                return Err(Error::InvalidParameter(Operation::ScrollSetup));
             }
-            DisplayRotation::Rotate180 => {
-                Command::SegmentRemap(false).send(&mut self.iface)?;
-                Command::ReverseComDir(false).send(&mut self.iface)
+        }
+
+        self.unlocked(Operation::StartScroll, |props| {
+            if let Some(setup) = props.scroll_setup {
+                // The setup command must only be sent while scrolling is deactivated
+                props.send_stop_scroll()?;
+
+                if let Command::VerticalScrollSetup(..) = setup {
+                    let (fixed_rows, scroll_rows) = props.vertical_scroll_area;
+                    Command::VerticalScrollArea(fixed_rows, scroll_rows).send(&mut props.iface)?;
+                }
+
+                setup.send(&mut props.iface)?;
+                Command::ScrollActive(true).send(&mut props.iface)?;
+                props.scroll_active = true;
             }
-            DisplayRotation::Rotate270 => {
-                Command::SegmentRemap(true).send(&mut self.iface)?;
-                Command::ReverseComDir(false).send(&mut self.iface)
+
+            Ok(())
+        })
+    }
+
+    /// Stop any active scroll. The display RAM content must be rewritten afterwards.
+    pub fn stop_scroll(&mut self) -> Result<(), Error> {
+        self.unlocked(Operation::StopScroll, Self::send_stop_scroll)
+    }
+
+    /// Whether a scroll is currently active
+    pub fn is_scrolling(&self) -> bool {
+        self.scroll_active
+    }
+
+    /// Enable or disable the command lock. While locked, the controller ignores all commands and
+    /// data except for the unlock command, which protects its configuration from noise on the
+    /// bus. All methods of the driver transparently unlock the controller while communicating
+    /// with it and lock it again afterwards.
+    pub fn set_command_lock(&mut self, lock: bool) -> Result<(), Error> {
+        Command::Lock(lock)
+            .send(&mut self.iface)
+            .map_err(|error| Error::Interface(Operation::SetCommandLock, error))?;
+        self.command_lock = lock;
+
+        Ok(())
+    }
+
+    /// Whether the command lock is enabled
+    pub fn is_command_locked(&self) -> bool {
+        self.command_lock
+    }
+
+    /// Send a low-level command to the display, unlocking the controller if needed. The driver
+    /// doesn't track any state changed by the command.
+    pub fn send_command(&mut self, command: Command) -> Result<(), Error> {
+        self.unlocked(Operation::Command, |props| command.send(&mut props.iface))
+    }
+
+    /// Send the built-in init sequence. The init-time parameters must have been validated.
+    fn send_init_sequence(&mut self) -> Result<(), DisplayError> {
+        let display_size = self.display_size;
+        let config = self.config;
+        let multiplex = self.multiplex();
+
+        let display_offset = config
+            .display_offset
+            .unwrap_or_else(|| display_size.row_offset());
+
+        Command::DisplayClockDiv(config.clock.0, config.clock.1).send(&mut self.iface)?;
+        Command::Multiplex(multiplex - 1).send(&mut self.iface)?;
+        Command::DisplayOffset(display_offset).send(&mut self.iface)?;
+        Command::StartLine(config.start_line).send(&mut self.iface)?;
+        Command::ComPinConfig(
+            config
+                .wiring
+                .com_alternative
+                .unwrap_or_else(|| display_size.com_pin_alternative()),
+            config.wiring.com_left_right_remap,
+        )
+        .send(&mut self.iface)?;
+        Command::AddressMode(self.addr_mode).send(&mut self.iface)?;
+
+        self.send_orientation(self.display_rotation, self.mirror)?;
+
+        if self.zoom {
+            Command::Zoom(true).send(&mut self.iface)?;
+        }
+
+        self.send_contrast(config.contrast)?;
+        Command::PreChargePeriod(config.precharge.0, config.precharge.1).send(&mut self.iface)?;
+        Command::VcomhDeselect(config.vcomh).send(&mut self.iface)?;
+        Command::ScrollActive(false).send(&mut self.iface)?;
+        self.scroll_active = false;
+        Command::DisplayOn(true).send(&mut self.iface)?;
+
+        Ok(())
+    }
+
+    /// Send a custom sequence of raw command bytes and update the tracked state accordingly
+    fn send_sequence<F>(&mut self, sequence: &'static [u8], send: F) -> Result<(), DisplayError>
+    where
+        F: FnOnce(&mut DI, &'static [u8]) -> Result<(), DisplayError>,
+    {
+        send(&mut self.iface, sequence)?;
+
+        // Track the remapping relative to the panel wiring
+        let wiring = self.config.wiring;
+        let (mut remap, mut reverse_com) = self.orientation_bits();
+
+        let mut commands = sequence;
+        while let Some(&opcode) = commands.first() {
+            let len = Command::len_of(opcode).unwrap_or(1).min(commands.len());
+            let args = &commands[1..len];
+
+            match (opcode, args) {
+                (0x20, &[0b00]) => self.addr_mode = AddrMode::Horizontal,
+                (0x20, &[0b01]) => self.addr_mode = AddrMode::Vertical,
+                (0x20, &[0b10]) => self.addr_mode = AddrMode::Page,
+                (0x81, &[contrast]) => self.contrast = contrast,
+                (0xA0 | 0xA1, _) => remap = (opcode & 0x01 != 0) ^ wiring.segment_remap,
+                (0xC0..=0xCF, _) => reverse_com = (opcode & 0x08 != 0) ^ wiring.reverse_com_dir,
+                (0xD6, &[zoom]) => self.zoom = zoom & 0x01 != 0,
+                (0x2E | 0x2F, _) => self.scroll_active = opcode == 0x2F,
+                (0x40..=0x7F, _) => self.config.start_line = opcode & 0x3F,
+                (0xA8, &[ratio]) if (15..=63).contains(&ratio) => {
+                    self.config.multiplex = Some(ratio + 1);
+                    self.vertical_scroll_area = (0, ratio + 1);
+                }
+                (0xFD, &[lock]) => self.command_lock = lock & 0x04 != 0,
+                _ => {}
             }
+
+            commands = &commands[len..];
         }
+
+        // The remap bits can't swap rows and columns, so keep the rotation within the same axis
+        // and express the rest as horizontal mirroring
+        (self.display_rotation, self.mirror) = match self.display_rotation {
+            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 if reverse_com => {
+                (DisplayRotation::Rotate0, (!remap, false))
+            }
+            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => {
+                (DisplayRotation::Rotate180, (remap, false))
+            }
+            DisplayRotation::Rotate90 | DisplayRotation::Rotate270 if remap => {
+                (DisplayRotation::Rotate270, (reverse_com, false))
+            }
+            DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => {
+                (DisplayRotation::Rotate90, (!reverse_com, false))
+            }
+        };
+
+        Ok(())
     }
 
-    /// Turn the display on or off. The display can be drawn to and retains all
-    /// of its memory even while off.
-    pub fn display_on(&mut self, on: bool) -> Result<(), DisplayError> {
-        Command::DisplayOn(on).send(&mut self.iface)
+    /// Get the segment remap and COM scan direction for the current rotation and mirroring,
+    /// relative to the panel wiring
+    fn orientation_bits(&self) -> (bool, bool) {
+        Self::orientation_bits_for(self.display_rotation, self.mirror)
     }
 
-    /// Set the display contrast
-    pub fn set_contrast(&mut self, contrast: u8) -> Result<(), DisplayError> {
-        Command::Contrast(contrast).send(&mut self.iface)
+    /// Get the segment remap and COM scan direction for a rotation and mirroring, relative to the
+    /// panel wiring
+    fn orientation_bits_for(rotation: DisplayRotation, mirror: (bool, bool)) -> (bool, bool) {
+        let (horizontal, vertical) = mirror;
+
+        // While rotated by 90 or 270 degrees, rows of the display run along the horizontal axis
+        match rotation {
+            DisplayRotation::Rotate0 => (!horizontal, !vertical),
+            DisplayRotation::Rotate90 => (vertical, !horizontal),
+            DisplayRotation::Rotate180 => (horizontal, vertical),
+            DisplayRotation::Rotate270 => (!vertical, horizontal),
+        }
+    }
+
+    /// Send the segment remap and COM scan direction for a rotation and mirroring, which become
+    /// the current ones once both commands have been sent
+    fn send_orientation(
+        &mut self,
+        rotation: DisplayRotation,
+        mirror: (bool, bool),
+    ) -> Result<(), DisplayError> {
+        let wiring = self.config.wiring;
+        let (remap, reverse_com) = Self::orientation_bits_for(rotation, mirror);
+
+        Command::SegmentRemap(remap ^ wiring.segment_remap).send(&mut self.iface)?;
+        Command::ReverseComDir(reverse_com ^ wiring.reverse_com_dir).send(&mut self.iface)?;
+        self.display_rotation = rotation;
+        self.mirror = mirror;
+
+        Ok(())
+    }
+
+    fn send_contrast(&mut self, contrast: u8) -> Result<(), DisplayError> {
+        Command::Contrast(contrast).send(&mut self.iface)?;
+        self.contrast = contrast;
+
+        Ok(())
+    }
+
+    fn send_stop_scroll(&mut self) -> Result<(), DisplayError> {
+        if self.scroll_active {
+            Command::ScrollActive(false).send(&mut self.iface)?;
+            self.scroll_active = false;
+        }
+
+        Ok(())
+    }
+
+    /// Get the multiplex ratio, which may be overridden by the init-time parameters
+    fn multiplex(&self) -> u8 {
+        self.config
+            .multiplex
+            .unwrap_or_else(|| self.display_size.multiplex())
+    }
+
+    /// Run `f` as `operation` with the controller unlocked if the command lock is enabled,
+    /// locking it again afterwards
+    fn unlocked<F>(&mut self, operation: Operation, f: F) -> Result<(), Error>
+    where
+        F: FnOnce(&mut Self) -> Result<(), DisplayError>,
+    {
+        let result = if self.command_lock {
+            Command::Lock(false).send(&mut self.iface).and_then(|_| {
+                let result = f(self);
+
+                // A custom init sequence may have disabled the lock
+                let relock = if self.command_lock {
+                    Command::Lock(true).send(&mut self.iface)
+                } else {
+                    Ok(())
+                };
+
+                result.and(relock)
+            })
+        } else {
+            f(self)
+        };
+
+        result.map_err(|error| Error::Interface(operation, error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::convert::TryFrom;
+
+    use super::DisplayProperties;
+    use crate::{
+        builder::Builder,
+        command::{AddrMode, Command, FadeMode, NFrames, Page, ScrollDirection},
+        config::{InitConfig, InitSequence},
+        displayrotation::DisplayRotation,
+        displaysize::{
+            CustomSize, DisplaySize128x32, DisplaySize128x64, DisplaySize64x48, DisplaySize72x40,
+            DisplaySize96x16, PanelSize,
+        },
+        error::{Error, Operation},
+        mode::{displaymode::DisplayModeTrait, GraphicsMode},
+        test_helpers::{command, RecordingInterface, StubInterface},
+        wiring::Wiring,
+    };
+
+    const VALUES: [u8; 11] = [0, 1, 7, 8, 63, 64, 65, 127, 128, 129, 255];
+
+    #[test]
+    fn invalid_input_does_not_panic() {
+        for row in 0..=255 {
+            assert_eq!(Page::try_from(row).is_ok(), row < 64);
+        }
+
+        let buffer = [0xff; 1025];
+
+        for &mode in &[AddrMode::Page, AddrMode::Horizontal, AddrMode::Vertical] {
+            let mut props =
+                DisplayProperties::new(StubInterface, DisplaySize128x64, DisplayRotation::Rotate0);
+            props.set_addr_mode(mode).unwrap();
+
+            assert!(props.draw(&buffer[..1]).is_err());
+
+            // An invalid draw area keeps the previous one
+            let mut area_set = false;
+
+            for &x0 in &VALUES {
+                for &y0 in &VALUES {
+                    for &x1 in &VALUES {
+                        for &y1 in &VALUES {
+                            let valid = x0 < x1 && x1 <= 128 && y0 < y1 && y1 <= 64;
+                            assert_eq!(props.set_draw_area((x0, y0), (x1, y1)).is_ok(), valid);
+                            area_set |= valid;
+
+                            for &len in &[0, 1, 3, 200, 1025] {
+                                assert_eq!(props.draw(&buffer[..len]).is_ok(), area_set);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn draw_streams_chunks() {
+        let mut source = [0; 300];
+        for (i, byte) in source.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+
+        for &mode in &[AddrMode::Page, AddrMode::Horizontal, AddrMode::Vertical] {
+            let record = |chunk_size: usize| {
+                let mut props = DisplayProperties::new(
+                    RecordingInterface::new(),
+                    DisplaySize128x64,
+                    DisplayRotation::Rotate0,
+                );
+                props.set_addr_mode(mode).unwrap();
+                props.set_draw_area((8, 8), (40, 40)).unwrap();
+
+                // Exactly fill the area, then wrap around to its start
+                for length in &[128, 172] {
+                    for chunk in source[..*length].chunks(chunk_size) {
+                        props.draw(chunk).unwrap();
+                    }
+                }
+
+                props.iface
+            };
+
+            let expected = record(source.len());
+            let data_len = expected.recorded().iter().filter(|&&b| b < 0x100).count();
+            assert_eq!(data_len, 300);
+            // No address is sent without data following it
+            assert!(expected.recorded().last().unwrap() < &0x100);
+
+            for &chunk_size in &[1, 37, 1000] {
+                assert_eq!(record(chunk_size).recorded(), expected.recorded());
+            }
+        }
+    }
+
+    #[test]
+    fn unaligned_rows_cover_same_pages() {
+        for &mode in &[AddrMode::Page, AddrMode::Horizontal, AddrMode::Vertical] {
+            let mut props = DisplayProperties::new(
+                RecordingInterface::new(),
+                DisplaySize128x64,
+                DisplayRotation::Rotate0,
+            );
+            props.set_addr_mode(mode).unwrap();
+            props.iface.len = 0;
+
+            props.set_draw_area((0, 4), (2, 12)).unwrap();
+            props.draw(&[1, 2, 3, 4]).unwrap();
+
+            let recorded = props.iface.recorded();
+            match mode {
+                AddrMode::Page => assert_eq!(
+                    recorded,
+                    [
+                        command(0xb0),
+                        command(0x00),
+                        command(0x10),
+                        1,
+                        2,
+                        command(0xb1),
+                        command(0x00),
+                        command(0x10),
+                        3,
+                        4,
+                    ]
+                ),
+                AddrMode::Horizontal | AddrMode::Vertical => assert_eq!(
+                    recorded,
+                    [
+                        command(0x21),
+                        command(0x00),
+                        command(0x01),
+                        command(0x22),
+                        command(0x00),
+                        command(0x01),
+                        1,
+                        2,
+                        3,
+                        4,
+                    ]
+                ),
+            }
+
+            // Both pages are filled, so the next data wraps around to the first page
+            props.iface.len = 0;
+            props.draw(&[5]).unwrap();
+            if let AddrMode::Page = mode {
+                assert_eq!(props.iface.recorded()[0], command(0xb0));
+            }
+            let position = match mode {
+                AddrMode::Vertical => (0, 1),
+                AddrMode::Page | AddrMode::Horizontal => (1, 0),
+            };
+            assert_eq!((props.draw_column, props.draw_row / 8), position);
+        }
+    }
+
+    #[test]
+    fn invalid_geometry_does_not_panic() {
+        for &width in &VALUES {
+            for &height in &VALUES {
+                let size = CustomSize::new(width, height).with_column_offset(8);
+                let props = DisplayProperties::new(StubInterface, size, DisplayRotation::Rotate90);
+                let mut display: GraphicsMode<_, CustomSize> = GraphicsMode::new(props);
+                let size = size.display_size();
+
+                for &x in &VALUES {
+                    display.set_pixel(x.into(), (255 - x).into(), 1);
+                }
+
+                assert_eq!(display.init().is_ok(), size.is_valid());
+                assert_eq!(display.flush().is_ok(), size.is_valid());
+                assert_eq!(display.flush_all().is_ok(), size.is_valid());
+                assert_eq!(display.flush_bytes().is_ok(), size.is_valid());
+            }
+        }
+    }
+
+    #[test]
+    fn scroll_limits() {
+        fn check_size<SIZE: PanelSize>(size: SIZE, multiplex: u8) {
+            let all = (Page::Page0, Page::Page7);
+            let vertical_scroll = |props: &mut DisplayProperties<_, SIZE>, offset| {
+                props.set_vertical_scroll(None, all, (0, 7), offset, NFrames::F2)
+            };
+            let horizontal_scroll = |props: &mut DisplayProperties<_, SIZE>, pages, columns| {
+                props.set_horizontal_scroll(ScrollDirection::Right, pages, columns, NFrames::F2)
+            };
+
+            let mut props = DisplayProperties::new(StubInterface, size, DisplayRotation::Rotate0);
+
+            assert!(props.set_vertical_scroll_area(multiplex - 1, 1).is_ok());
+            assert!(props.set_vertical_scroll_area(0, multiplex).is_ok());
+            assert!(props.set_vertical_scroll_area(multiplex + 1, 0).is_err());
+            assert!(props.set_vertical_scroll_area(0, multiplex + 1).is_err());
+            assert!(props.set_vertical_scroll_area(1, multiplex).is_err());
+
+            assert!(vertical_scroll(&mut props, 0).is_ok());
+            assert!(vertical_scroll(&mut props, multiplex - 1).is_ok());
+            assert!(vertical_scroll(&mut props, multiplex).is_err());
+
+            // The configured offset must stay smaller than the scroll area
+            assert!(props.set_vertical_scroll_area(1, 10).is_err());
+            assert!(vertical_scroll(&mut props, 0).is_ok());
+            assert!(props.set_vertical_scroll_area(1, 10).is_ok());
+            assert!(vertical_scroll(&mut props, 9).is_ok());
+            assert!(vertical_scroll(&mut props, 10).is_err());
+            assert!(props.set_vertical_scroll_area(0, 9).is_err());
+            assert_eq!(props.vertical_scroll_area, (1, 10));
+
+            assert!(horizontal_scroll(&mut props, all, (0, 127)).is_ok());
+            assert!(horizontal_scroll(&mut props, all, (0, 128)).is_err());
+            assert!(horizontal_scroll(&mut props, all, (5, 4)).is_err());
+            assert!(horizontal_scroll(&mut props, (Page::Page3, Page::Page3), (4, 4)).is_ok());
+            assert!(horizontal_scroll(&mut props, (Page::Page3, Page::Page2), (0, 127)).is_err());
+
+            // The display start line must be smaller than the scroll area
+            assert!(props.set_vertical_scroll_area(0, 0).is_err());
+            assert!(props.set_vertical_scroll_area(multiplex, 0).is_err());
+
+            let config = InitConfig {
+                start_line: 10,
+                ..InitConfig::default()
+            };
+            let mut props = DisplayProperties::new(StubInterface, size, DisplayRotation::Rotate0)
+                .with_config(config);
+            assert!(props.set_vertical_scroll_area(0, 10).is_err());
+            assert!(props.set_vertical_scroll_area(0, 11).is_ok());
+        }
+
+        check_size(DisplaySize128x64, 64);
+        check_size(DisplaySize128x32, 32);
+
+        // A start line moved by the init sequence is checked when the scroll is started
+        let all = (Page::Page0, Page::Page7);
+        let mut props = Builder::new()
+            .with_init_sequence(InitSequence::Append(&[0x40 | 20]))
+            .connect(StubInterface)
+            .release();
+        props.set_vertical_scroll_area(0, 16).unwrap();
+        props
+            .set_vertical_scroll(None, all, (0, 7), 1, NFrames::F2)
+            .unwrap();
+        props.start_scroll().unwrap();
+
+        props.init_column_mode().unwrap();
+        assert!(matches!(
+            props.start_scroll(),
+            Err(Error::InvalidParameter(Operation::ScrollSetup))
+        ));
+        assert!(!props.is_scrolling());
+    }
+
+    #[test]
+    fn init_programs_display_size() {
+        fn check_size<SIZE: PanelSize>(size: SIZE, multiplex: u8, offset: u8, com_pins: u8) {
+            let mut props = Builder::new()
+                .with_size(size)
+                .connect(RecordingInterface::new())
+                .release();
+            props.init_column_mode().unwrap();
+
+            assert_eq!(
+                props.iface.recorded()[2..9],
+                [
+                    command(0xa8),
+                    command(multiplex),
+                    command(0xd3),
+                    command(offset),
+                    command(0x40),
+                    command(0xda),
+                    command(com_pins),
+                ]
+            );
+        }
+
+        // Multiplex ratio - 1, display offset and COM pin configuration of each size
+        check_size(DisplaySize128x64, 0x3f, 0x00, 0x12);
+        check_size(DisplaySize128x32, 0x1f, 0x00, 0x02);
+        check_size(DisplaySize96x16, 0x0f, 0x00, 0x02);
+        check_size(DisplaySize64x48, 0x2f, 0x00, 0x12);
+        check_size(DisplaySize72x40, 0x27, 0x00, 0x12);
+    }
+
+    #[test]
+    fn fade_interval_limits() {
+        let mut props = DisplayProperties::new(
+            RecordingInterface::new(),
+            DisplaySize128x64,
+            DisplayRotation::Rotate0,
+        );
+
+        assert!(matches!(
+            props.set_fade(FadeMode::Blink, 16),
+            Err(Error::InvalidParameter(Operation::SetFade))
+        ));
+        assert_eq!(props.iface.len, 0);
+
+        props.set_fade(FadeMode::Blink, 15).unwrap();
+        props.set_fade(FadeMode::FadeOut, 0).unwrap();
+        assert_eq!(props.iface.recorded(), [0x123, 0x13f, 0x123, 0x120]);
+    }
+
+    #[test]
+    fn commands_are_wrapped_in_unlock_and_lock() {
+        let mut props = DisplayProperties::new(
+            RecordingInterface::new(),
+            DisplaySize128x64,
+            DisplayRotation::Rotate0,
+        );
+
+        props.set_contrast(0x40).unwrap();
+        props.set_command_lock(true).unwrap();
+        props.set_contrast(0x50).unwrap();
+        props.send_command(Command::DisplayOn(true)).unwrap();
+        props.set_command_lock(false).unwrap();
+        props.display_on(false).unwrap();
+
+        assert_eq!(
+            props.iface.recorded(),
+            [
+                0x181, 0x140, // Unlocked contrast
+                0x1fd, 0x116, // Lock
+                0x1fd, 0x112, 0x181, 0x150, 0x1fd, 0x116, // Contrast
+                0x1fd, 0x112, 0x1af, 0x1fd, 0x116, // Display on
+                0x1fd, 0x112, // Unlock
+                0x1ae, // Unlocked display off
+            ]
+        );
+
+        props.set_command_lock(true).unwrap();
+        let (len, transfers) = (props.iface.len, props.iface.transfers);
+        props.iface.fail_after(0);
+
+        assert!(matches!(
+            props.set_contrast(0x60),
+            Err(Error::Interface(Operation::SetContrast, _))
+        ));
+        assert_eq!(props.iface.len, len);
+        assert_eq!(props.iface.transfers, transfers + 1);
+        assert_eq!(props.get_contrast(), 0x50);
+    }
+
+    #[test]
+    fn keeps_orientation_until_sent() {
+        let mut props = DisplayProperties::new(
+            RecordingInterface::new(),
+            DisplaySize128x64,
+            DisplayRotation::Rotate0,
+        );
+
+        // The segment remap is sent, the COM scan direction fails
+        props.iface.fail_after(1);
+        assert!(matches!(
+            props.set_rotation(DisplayRotation::Rotate90),
+            Err(Error::Interface(Operation::SetOrientation, _))
+        ));
+        props.iface.fail_after(0);
+        assert!(props.set_mirror(true, false).is_err());
+
+        assert!(matches!(props.get_rotation(), DisplayRotation::Rotate0));
+        assert_eq!(props.get_mirror(), (false, false));
+
+        let mut display: GraphicsMode<_> = GraphicsMode::new(props);
+        display.properties_mut().iface_mut().budget = None;
+        display.flush_all().unwrap();
+        display.properties_mut().iface_mut().fail_after(0);
+
+        assert!(display.set_rotation(DisplayRotation::Rotate180).is_err());
+        assert!(display.set_mirror(false, true).is_err());
+        assert_eq!(display.dirty_bytes(), 0);
+    }
+
+    #[test]
+    fn validates_and_tracks_custom_sequences() {
+        // Unknown opcodes like the charge pump of the SSD1306, and truncated commands
+        let sequences = [
+            &[0xAE, 0xE0][..],
+            &[0x8D, 0x14],
+            &[0xAE, 0x81],
+            &[0x26, 0, 0, 7, 7, 0, 0],
+        ];
+
+        for sequence in sequences {
+            let mut props = Builder::new()
+                .with_init_sequence(InitSequence::Replace(sequence))
+                .connect(RecordingInterface::new())
+                .release();
+
+            assert!(matches!(
+                props.init_column_mode(),
+                Err(Error::InvalidParameter(Operation::Init))
+            ));
+            assert_eq!(props.iface.len, 0);
+        }
+
+        // Multiplex ratio of 32 and lock
+        let mut props = Builder::new()
+            .with_init_sequence(InitSequence::Replace(&[0xA8, 0x1F, 0xFD, 0x16]))
+            .connect(RecordingInterface::new())
+            .release();
+        props.init_column_mode().unwrap();
+
+        assert!(props.is_command_locked());
+        assert!(props.set_vertical_scroll_area(0, 33).is_err());
+        assert!(props.set_vertical_scroll_area(1, 31).is_ok());
+
+        props.iface.len = 0;
+        props.display_on(true).unwrap();
+        assert_eq!(props.iface.recorded(), [0x1fd, 0x112, 0x1af, 0x1fd, 0x116]);
+
+        // A sequence unlocking the controller is not followed by the lock of the driver
+        let mut props = Builder::new()
+            .with_init_sequence(InitSequence::Append(&[0xFD, 0x12]))
+            .connect(RecordingInterface::new())
+            .release();
+        props.set_command_lock(true).unwrap();
+        props.init_column_mode().unwrap();
+
+        assert!(!props.is_command_locked());
+        assert_eq!(
+            props.iface.recorded()[props.iface.len - 2..],
+            [0x1fd, 0x112]
+        );
+    }
+
+    #[test]
+    fn orientation_bits_for_all_orientations() {
+        use DisplayRotation::*;
+
+        // Segment remap and reverse COM scan direction for a panel wired without remapping
+        let orientations = [
+            (Rotate0, (false, false), (true, true)),
+            (Rotate0, (true, false), (false, true)),
+            (Rotate0, (false, true), (true, false)),
+            (Rotate0, (true, true), (false, false)),
+            (Rotate90, (false, false), (false, true)),
+            (Rotate90, (true, false), (false, false)),
+            (Rotate90, (false, true), (true, true)),
+            (Rotate90, (true, true), (true, false)),
+            (Rotate180, (false, false), (false, false)),
+            (Rotate180, (true, false), (true, false)),
+            (Rotate180, (false, true), (false, true)),
+            (Rotate180, (true, true), (true, true)),
+            (Rotate270, (false, false), (true, false)),
+            (Rotate270, (true, false), (true, true)),
+            (Rotate270, (false, true), (false, false)),
+            (Rotate270, (true, true), (false, true)),
+        ];
+
+        for (segment_remap, reverse_com_dir) in
+            [(false, false), (true, false), (false, true), (true, true)]
+        {
+            let wiring = Wiring::new()
+                .with_segment_remap(segment_remap)
+                .with_reverse_com_dir(reverse_com_dir);
+
+            for &(rotation, (horizontal, vertical), (remap, reverse_com)) in &orientations {
+                let mut props = Builder::new()
+                    .with_wiring(wiring)
+                    .connect(RecordingInterface::new())
+                    .release();
+                props.set_rotation(rotation).unwrap();
+                props.set_mirror(horizontal, vertical).unwrap();
+
+                let remap = 0x1a0 | (remap ^ segment_remap) as u16;
+                let reverse_com = 0x1c0 | ((reverse_com ^ reverse_com_dir) as u16) << 3;
+                assert_eq!(props.iface.recorded()[2..], [remap, reverse_com]);
+            }
+        }
     }
 }
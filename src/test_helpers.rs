@@ -5,6 +5,7 @@
 use display_interface::{DisplayError, WriteOnlyDataCommand};
 use embedded_hal::{
     blocking::{
+        delay::DelayMs,
         i2c,
         spi::{self, Transfer},
     },
@@ -59,6 +60,66 @@ impl OutputPin for PinStub {
     }
 }
 
+#[cfg(feature = "embedded-hal-1")]
+impl embedded_hal_1::digital::ErrorType for PinStub {
+    type Error = core::convert::Infallible;
+}
+
+#[cfg(feature = "embedded-hal-1")]
+impl embedded_hal_1::digital::OutputPin for PinStub {
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "embedded-hal-1")]
+impl embedded_hal_1::spi::ErrorType for SpiStub {
+    type Error = core::convert::Infallible;
+}
+
+#[cfg(feature = "embedded-hal-1")]
+impl embedded_hal_1::spi::SpiDevice for SpiStub {
+    fn transaction(
+        &mut self,
+        _operations: &mut [embedded_hal_1::spi::Operation<'_, u8>],
+    ) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "embedded-hal-1")]
+impl embedded_hal_1::i2c::ErrorType for I2cStub {
+    type Error = core::convert::Infallible;
+}
+
+#[cfg(feature = "embedded-hal-1")]
+impl embedded_hal_1::i2c::I2c for I2cStub {
+    fn transaction(
+        &mut self,
+        _address: u8,
+        _operations: &mut [embedded_hal_1::i2c::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy)]
+pub struct DelayStub;
+
+impl DelayMs<u8> for DelayStub {
+    fn delay_ms(&mut self, _ms: u8) {}
+}
+
+#[cfg(feature = "embedded-hal-1")]
+impl embedded_hal_1::delay::DelayNs for DelayStub {
+    fn delay_ns(&mut self, _ns: u32) {}
+}
+
 #[allow(dead_code)]
 #[derive(Debug, Clone, Copy)]
 pub struct StubInterface;
@@ -74,3 +135,127 @@ impl WriteOnlyDataCommand for StubInterface {
         Ok(())
     }
 }
+
+/// Flag set by [RecordingInterface] on recorded command bytes
+const COMMAND_FLAG: u16 = 0x100;
+
+/// Get the entry recorded by [RecordingInterface] for a command byte
+#[allow(dead_code)]
+pub fn command(byte: u8) -> u16 {
+    COMMAND_FLAG | byte as u16
+}
+
+/// Create a graphics mode driver for a 128x64 display recording to a [RecordingInterface] and
+/// initialise it
+#[cfg(test)]
+pub fn initialised_display() -> crate::mode::GraphicsMode<RecordingInterface> {
+    let mut display: crate::mode::GraphicsMode<_> = crate::Builder::new()
+        .connect(RecordingInterface::new())
+        .into();
+    display.init().unwrap();
+
+    display
+}
+
+/// Interface recording every byte sent to it. Command bytes are recorded with bit 8 set to tell
+/// them apart from data bytes.
+#[allow(dead_code)]
+#[derive(Clone, Copy)]
+pub struct RecordingInterface {
+    pub log: [u16; 4096],
+    pub len: usize,
+    /// Number of transfers attempted, including failed ones
+    pub transfers: usize,
+    /// Number of transfers which succeed before all further transfers fail
+    pub budget: Option<usize>,
+}
+
+#[allow(dead_code)]
+impl RecordingInterface {
+    pub fn new() -> Self {
+        RecordingInterface {
+            log: [0; 4096],
+            len: 0,
+            transfers: 0,
+            budget: None,
+        }
+    }
+
+    /// Let the next `transfers` transfers succeed and fail all transfers after them
+    pub fn fail_after(&mut self, transfers: usize) {
+        self.budget = Some(transfers);
+    }
+
+    pub fn recorded(&self) -> &[u16] {
+        &self.log[..self.len]
+    }
+
+    fn record(
+        &mut self,
+        buf: display_interface::DataFormat<'_>,
+        flag: u16,
+    ) -> Result<(), DisplayError> {
+        self.transfers += 1;
+        match self.budget {
+            Some(0) => return Err(DisplayError::BusWriteError),
+            Some(ref mut budget) => *budget -= 1,
+            None => {}
+        }
+
+        match buf {
+            display_interface::DataFormat::U8(bytes) => {
+                for &byte in bytes {
+                    let entry = self
+                        .log
+                        .get_mut(self.len)
+                        .ok_or(DisplayError::BusWriteError)?;
+                    *entry = flag | byte as u16;
+                    self.len += 1;
+                }
+
+                Ok(())
+            }
+            _ => Err(DisplayError::DataFormatNotImplemented),
+        }
+    }
+}
+
+impl WriteOnlyDataCommand for RecordingInterface {
+    fn send_commands(
+        &mut self,
+        cmd: display_interface::DataFormat<'_>,
+    ) -> Result<(), DisplayError> {
+        self.record(cmd, COMMAND_FLAG)
+    }
+
+    fn send_data(&mut self, buf: display_interface::DataFormat<'_>) -> Result<(), DisplayError> {
+        self.record(buf, 0)
+    }
+}
+
+#[cfg(feature = "async")]
+impl display_interface_async::AsyncWriteOnlyDataCommand for RecordingInterface {
+    async fn send_commands(
+        &mut self,
+        cmd: display_interface_async::DataFormat<'_>,
+    ) -> Result<(), display_interface_async::DisplayError> {
+        match cmd {
+            display_interface_async::DataFormat::U8(bytes) => self
+                .record(display_interface::DataFormat::U8(bytes), COMMAND_FLAG)
+                .map_err(|_| display_interface_async::DisplayError::BusWriteError),
+            _ => Err(display_interface_async::DisplayError::DataFormatNotImplemented),
+        }
+    }
+
+    async fn send_data(
+        &mut self,
+        buf: display_interface_async::DataFormat<'_>,
+    ) -> Result<(), display_interface_async::DisplayError> {
+        match buf {
+            display_interface_async::DataFormat::U8(bytes) => self
+                .record(display_interface::DataFormat::U8(bytes), 0)
+                .map_err(|_| display_interface_async::DisplayError::BusWriteError),
+            _ => Err(display_interface_async::DisplayError::DataFormatNotImplemented),
+        }
+    }
+}
@@ -0,0 +1,224 @@
+//! Software-driven ticker
+//!
+//! A [Ticker] moves content through an area of the display one column at a time using the
+//! controller's content scroll commands. Each step only sends the scroll command and the single
+//! column of pixels that is revealed, instead of a full frame. Timing is left to the caller, which
+//! makes frame-exact tickers possible.
+//!
+//! ```rust
+//!# #[path = "test_helpers.rs"]
+//!# mod test_helpers;
+//!# use test_helpers::StubInterface;
+//!# let interface = StubInterface;
+//! use ssd1309::{prelude::*, ticker::Ticker};
+//!
+//! // A 256 column wide, one page high strip of pixels, e.g. some prerendered text
+//! let text = [0x3c; 256];
+//!
+//! let mut display: GraphicsMode<_> = ssd1309::Builder::new().connect(interface).into();
+//! let mut ticker = Ticker::new(&text, ScrollDirection::Left, (Page::Page7, Page::Page7), (0, 127));
+//!
+//! display.init().unwrap();
+//!
+//! for _ in 0..text.len() {
+//!     display.step_ticker(&mut ticker).unwrap();
+//!     // Wait for at least two frames
+//! }
+//! ```
+
+use display_interface::WriteOnlyDataCommand;
+
+use crate::{
+    command::{Page, ScrollDirection},
+    displaysize::PanelSize,
+    error::{Error, Operation},
+    properties::DisplayProperties,
+};
+
+/// Ticker moving a source image through an area of the display one column per step
+#[derive(Clone, Copy)]
+pub struct Ticker<'a> {
+    source: &'a [u8],
+    direction: ScrollDirection,
+    pages: (Page, Page),
+    columns: (u8, u8),
+    position: usize,
+}
+
+impl<'a> Ticker<'a> {
+    /// Create a new ticker moving `source` in `direction` through the given inclusive page and
+    /// column ranges. Columns are counted from the left edge of the panel and are not affected by
+    /// the display rotation.
+    ///
+    /// `source` uses the same layout as the display RAM: one row of bytes per page, each byte
+    /// holding a column of 8 pixels. Its width is the length divided by the number of pages. The
+    /// source repeats once its end has been reached.
+    pub fn new(
+        source: &'a [u8],
+        direction: ScrollDirection,
+        pages: (Page, Page),
+        columns: (u8, u8),
+    ) -> Self {
+        Ticker {
+            source,
+            direction,
+            pages,
+            columns,
+            position: 0,
+        }
+    }
+
+    /// Move the content by one column and draw the next column of the source into the revealed
+    /// column. The datasheet requires at least two frames between consecutive steps.
+    pub fn step<DI, SIZE>(
+        &mut self,
+        properties: &mut DisplayProperties<DI, SIZE>,
+    ) -> Result<(), Error>
+    where
+        DI: WriteOnlyDataCommand,
+        SIZE: PanelSize,
+    {
+        let source_width = self.source_width(properties)?;
+        let (start_page, end_page) = (self.pages.0 as usize, self.pages.1 as usize);
+        let page_count = end_page + 1 - start_page;
+
+        properties.scroll_content(self.direction, self.pages, self.columns)?;
+
+        let column = match self.direction {
+            ScrollDirection::Left => self.columns.1,
+            ScrollDirection::Right => self.columns.0,
+        };
+        let data = self.next_column(source_width);
+
+        let column = column + properties.column_offset();
+        properties.set_draw_area(
+            (column, (start_page * 8) as u8),
+            (column + 1, ((end_page + 1) * 8) as u8),
+        )?;
+        properties.draw(&data[..page_count])?;
+
+        self.position = (self.position + 1) % source_width;
+
+        Ok(())
+    }
+
+    /// Check the source and the area of the ticker, returning the width of the source
+    pub(crate) fn source_width<DI, SIZE>(
+        &self,
+        properties: &DisplayProperties<DI, SIZE>,
+    ) -> Result<usize, Error>
+    where
+        DI: WriteOnlyDataCommand,
+        SIZE: PanelSize,
+    {
+        let (start_page, end_page) = (self.pages.0 as usize, self.pages.1 as usize);
+        let page_count = (end_page + 1).saturating_sub(start_page);
+        let source_width = self.source.len() / page_count.max(1);
+
+        if source_width == 0
+            || self.source.len() != source_width * page_count
+            || properties
+                .content_scroll_columns(self.pages, self.columns)
+                .is_none()
+        {
+            return Err(Error::InvalidParameter(Operation::ScrollContent));
+        }
+
+        Ok(source_width)
+    }
+
+    /// Get the column of the source revealed by the next step, one byte per page of the ticker
+    pub(crate) fn next_column(&self, source_width: usize) -> [u8; 8] {
+        let source_column = match self.direction {
+            ScrollDirection::Left => self.position,
+            ScrollDirection::Right => source_width - 1 - self.position,
+        };
+        let page_count = self.pages.1 as usize + 1 - self.pages.0 as usize;
+
+        let mut data = [0; 8];
+        for (page, byte) in data.iter_mut().take(page_count).enumerate() {
+            *byte = self.source[page * source_width + source_column];
+        }
+
+        data
+    }
+
+    /// Get the inclusive page and column ranges the ticker moves through
+    pub(crate) fn area(&self) -> ((Page, Page), (u8, u8)) {
+        (self.pages, self.columns)
+    }
+
+    /// Restart the ticker from the beginning of the source
+    pub fn reset(&mut self) {
+        self.position = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Ticker;
+    use crate::{
+        command::{AddrMode, Page, ScrollDirection},
+        displayrotation::DisplayRotation,
+        displaysize::DisplaySize128x64,
+        properties::DisplayProperties,
+        test_helpers::RecordingInterface,
+    };
+
+    fn step(props: &mut DisplayProperties<RecordingInterface>, ticker: &mut Ticker) -> [u16; 16] {
+        props.iface_mut().len = 0;
+        ticker.step(props).unwrap();
+
+        let iface = props.iface_mut();
+        assert_eq!(iface.len, 16);
+
+        let mut log = [0; 16];
+        log.copy_from_slice(&iface.log[..16]);
+        log
+    }
+
+    #[test]
+    fn scrolls_and_draws_one_column_per_step() {
+        // Two pages of a source four columns wide
+        let source = [1, 2, 3, 4, 11, 12, 13, 14];
+        let pages = (Page::Page6, Page::Page7);
+
+        let mut props = DisplayProperties::new(
+            RecordingInterface::new(),
+            DisplaySize128x64,
+            DisplayRotation::Rotate0,
+        );
+        props.set_addr_mode(AddrMode::Horizontal).unwrap();
+
+        let mut ticker = Ticker::new(&source, ScrollDirection::Left, pages, (8, 39));
+        assert_eq!(
+            step(&mut props, &mut ticker),
+            [
+                0x12d, 0x100, 0x106, 0x101, 0x107, 0x100, 0x108, 0x127, // Scroll left
+                0x121, 0x127, 0x127, 0x122, 0x106, 0x107, // Revealed column on the right
+                1, 11,
+            ]
+        );
+        assert_eq!(step(&mut props, &mut ticker)[14..], [2, 12]);
+        assert_eq!(step(&mut props, &mut ticker)[14..], [3, 13]);
+        assert_eq!(step(&mut props, &mut ticker)[14..], [4, 14]);
+        assert_eq!(step(&mut props, &mut ticker)[14..], [1, 11]);
+
+        ticker.reset();
+        assert_eq!(step(&mut props, &mut ticker)[14..], [1, 11]);
+
+        let mut ticker = Ticker::new(&source, ScrollDirection::Right, pages, (8, 39));
+        assert_eq!(
+            step(&mut props, &mut ticker),
+            [
+                0x12c, 0x100, 0x106, 0x101, 0x107, 0x100, 0x108, 0x127, // Scroll right
+                0x121, 0x108, 0x108, 0x122, 0x106, 0x107, // Revealed column on the left
+                4, 14,
+            ]
+        );
+        assert_eq!(step(&mut props, &mut ticker)[14..], [3, 13]);
+
+        ticker.reset();
+        assert_eq!(step(&mut props, &mut ticker)[14..], [4, 14]);
+    }
+}
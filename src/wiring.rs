@@ -0,0 +1,102 @@
+//! Panel wiring
+
+/// Description of how the panel is wired to the controller. Boards which route the panel
+/// flipped or with swapped COM pins need a matching wiring to show rows and columns in the right
+/// order. The display rotation is applied on top of the wiring.
+///
+/// ```rust
+/// use ssd1309::prelude::*;
+///
+/// let wiring = Wiring::new().with_reverse_com_dir(true).with_com_left_right_remap(true);
+/// let builder = ssd1309::Builder::new().with_wiring(wiring);
+/// ```
+#[derive(Clone, Copy, Default)]
+pub struct Wiring {
+    pub(crate) segment_remap: bool,
+    pub(crate) reverse_com_dir: bool,
+    pub(crate) com_alternative: Option<bool>,
+    pub(crate) com_left_right_remap: bool,
+}
+
+impl Wiring {
+    /// Create a new wiring without any remapping and the COM pin configuration of the display
+    /// size
+    pub const fn new() -> Self {
+        Wiring {
+            segment_remap: false,
+            reverse_com_dir: false,
+            com_alternative: None,
+            com_left_right_remap: false,
+        }
+    }
+
+    /// Set whether the segments are connected in reverse order
+    pub const fn with_segment_remap(self, segment_remap: bool) -> Self {
+        Self {
+            segment_remap,
+            ..self
+        }
+    }
+
+    /// Set whether the COM lines are connected in reverse order
+    pub const fn with_reverse_com_dir(self, reverse_com_dir: bool) -> Self {
+        Self {
+            reverse_com_dir,
+            ..self
+        }
+    }
+
+    /// Set whether the panel uses the alternative (`true`) or sequential (`false`) COM pin
+    /// configuration, overriding the one of the display size
+    pub const fn with_com_alternative(self, com_alternative: bool) -> Self {
+        Self {
+            com_alternative: Some(com_alternative),
+            ..self
+        }
+    }
+
+    /// Set whether the left and right COM pins are swapped
+    pub const fn with_com_left_right_remap(self, com_left_right_remap: bool) -> Self {
+        Self {
+            com_left_right_remap,
+            ..self
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Wiring;
+    use crate::{
+        builder::Builder,
+        test_helpers::{command, RecordingInterface},
+    };
+
+    #[test]
+    fn sends_com_left_right_remap() {
+        // COM pin configuration of the 128 by 64 display with each wiring
+        let wirings = [
+            (Wiring::new(), 0x12),
+            (Wiring::new().with_com_left_right_remap(true), 0x32),
+            (
+                Wiring::new()
+                    .with_com_alternative(false)
+                    .with_com_left_right_remap(true),
+                0x22,
+            ),
+        ];
+
+        for (wiring, com_pins) in wirings {
+            let mut properties = Builder::new()
+                .with_wiring(wiring)
+                .connect(RecordingInterface::new())
+                .release();
+            properties.init_column_mode().unwrap();
+
+            assert_eq!(
+                properties.iface_mut().recorded()[7..9],
+                [command(0xda), command(com_pins)]
+            );
+        }
+    }
+}